@@ -14,4 +14,7 @@ cp target/wasm32-wasi/release/fevm_json_rpc.wasm artifacts/
 cp target/wasm32-wasi/release/fevm_json_rpc.wasm builtin-package/
 marine aqua artifacts/fevm_json_rpc.wasm -s fevm_json_rpc -i fevm_json_rpc > ./aqua/fevm_json_rpc.aqua
 
+# request bodies too large for the curl command line are spooled here
+mkdir -p /tmp/fevm_json_rpc
+
 RUST_LOG="info" mrepl --quiet Config.toml
\ No newline at end of file
@@ -1,3 +1,9 @@
+# The module is built by build.sh, the repository ships no prebuilt wasm
+if [ ! -f artifacts/fevm_json_rpc.wasm ]; then
+  echo "artifacts/fevm_json_rpc.wasm is missing, run ./build.sh first" >&2
+  exit 1
+fi
+
 aqua remote deploy_service \
 --addr $1 \
 --sk $2 \
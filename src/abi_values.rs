@@ -0,0 +1,473 @@
+use crate::error::{Error, Result};
+use crate::eth_utils::strip_hex_prefix;
+use crate::models::log_param::DataLogParam;
+use ethabi::param_type::Reader;
+use ethabi::{ParamType, Token};
+use ethereum_types::{H160, U256};
+use serde_json::{Map, Value};
+
+/**
+ * Canonical form of a Solidity type name, `uint` and `int` being aliases of
+ * their 256 bit versions
+ */
+pub fn canonical_type(type_name: &str) -> String {
+    let type_name: String = type_name.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut canonical = String::with_capacity(type_name.len());
+    let mut word = String::new();
+
+    // Aliases may appear anywhere in array and tuple types, e.g. `(uint,int[])`
+    for c in type_name.chars().chain(std::iter::once(',')) {
+        if c.is_ascii_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+        match word.as_str() {
+            "uint" | "int" => canonical.push_str(&format!("{}256", word)),
+            _ => canonical.push_str(&word),
+        }
+        word.clear();
+        canonical.push(c);
+    }
+    canonical.pop();
+
+    canonical
+}
+
+/**
+ * Parse a Solidity type such as `uint8`, `bytes32[2]` or `(address,uint256)[]`
+ */
+pub fn parse_type(type_name: &str) -> Result<ParamType> {
+    let unknown = || Error::InvalidArgument(format!("unknown ABI type {:?}", type_name));
+
+    // The reader reads names it does not know as enums, i.e. `uint8`
+    let names_known = type_name
+        .split(|c: char| "()[],".contains(c))
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && !name.chars().all(|c| c.is_ascii_digit()))
+        .all(is_known_name);
+    if type_name.trim().is_empty() || !names_known {
+        return Err(unknown());
+    }
+
+    let param_type = Reader::read(&canonical_type(type_name)).map_err(|_| unknown())?;
+    if !is_valid_type(&param_type) {
+        return Err(unknown());
+    }
+    Ok(param_type)
+}
+
+fn is_known_name(name: &str) -> bool {
+    let sized = |prefix: &str| {
+        name.strip_prefix(prefix)
+            .is_some_and(|size| size.chars().all(|c| c.is_ascii_digit()))
+    };
+    ["address", "bool", "string"].contains(&name) || sized("uint") || sized("int") || sized("bytes")
+}
+
+// The reader accepts any size, e.g. `uint7` or `bytes33`
+fn is_valid_type(param_type: &ParamType) -> bool {
+    match param_type {
+        ParamType::Uint(bits) | ParamType::Int(bits) => (8..=256).contains(bits) && bits % 8 == 0,
+        ParamType::FixedBytes(size) => (1..=32).contains(size),
+        ParamType::Array(inner) | ParamType::FixedArray(inner, _) => is_valid_type(inner),
+        ParamType::Tuple(types) => !types.is_empty() && types.iter().all(is_valid_type),
+        ParamType::Address | ParamType::Bool | ParamType::String | ParamType::Bytes => true,
+    }
+}
+
+/**
+ * Token of an argument given as text: scalars as they are written (`0x12..`,
+ * `-5`, `true`, `hello`), arrays and tuples as JSON arrays
+ */
+pub fn tokenize_arg(param_type: &ParamType, value: &str, path: &str) -> Result<Token> {
+    let value = match param_type {
+        ParamType::Array(_) | ParamType::FixedArray(_, _) | ParamType::Tuple(_) => {
+            serde_json::from_str(value).map_err(|err| {
+                Error::InvalidArgument(format!(
+                    "{}: {} expects a JSON array, {}",
+                    path, param_type, err
+                ))
+            })?
+        }
+        _ => Value::String(value.to_string()),
+    };
+
+    tokenize(param_type, &value, path)
+}
+
+fn mismatch(param_type: &ParamType, value: &Value, path: &str, reason: &str) -> Error {
+    Error::InvalidArgument(format!(
+        "{}: {} can't be encoded as {}, {}",
+        path, value, param_type, reason
+    ))
+}
+
+/**
+ * Token of a JSON value, `path` names the value in errors
+ */
+pub fn tokenize(param_type: &ParamType, value: &Value, path: &str) -> Result<Token> {
+    let err = |reason: &str| mismatch(param_type, value, path, reason);
+
+    match param_type {
+        ParamType::Address => {
+            let bytes = hex_bytes(value).ok_or_else(|| err("expected 0x and 40 hex digits"))?;
+            if bytes.len() != 20 {
+                return Err(err("expected 0x and 40 hex digits"));
+            }
+            Ok(Token::Address(H160::from_slice(&bytes)))
+        }
+        ParamType::Bool => match value {
+            Value::Bool(value) => Ok(Token::Bool(*value)),
+            Value::String(value) if value == "true" => Ok(Token::Bool(true)),
+            Value::String(value) if value == "false" => Ok(Token::Bool(false)),
+            _ => Err(err("expected true or false")),
+        },
+        ParamType::String => match value {
+            Value::String(value) => Ok(Token::String(value.clone())),
+            _ => Err(err("expected a string")),
+        },
+        ParamType::Bytes => hex_bytes(value)
+            .map(Token::Bytes)
+            .ok_or_else(|| err("expected 0x followed by whole bytes")),
+        ParamType::FixedBytes(size) => {
+            let bytes = hex_bytes(value).ok_or_else(|| err("expected 0x followed by hex bytes"))?;
+            if bytes.len() != *size {
+                return Err(err(&format!(
+                    "expected {} bytes, got {}",
+                    size,
+                    bytes.len()
+                )));
+            }
+            Ok(Token::FixedBytes(bytes))
+        }
+        ParamType::Uint(bits) => {
+            let value = unsigned(value).ok_or_else(|| err("expected an unsigned integer"))?;
+            if value.bits() > *bits {
+                return Err(err(&format!("doesn't fit in {} bits", bits)));
+            }
+            Ok(Token::Uint(value))
+        }
+        ParamType::Int(bits) => {
+            let (negative, abs) = signed(value).ok_or_else(|| err("expected an integer"))?;
+            // int<bits> holds -2^(bits-1) ..= 2^(bits-1) - 1
+            let limit = U256::one() << (bits - 1);
+            if (negative && abs > limit) || (!negative && abs >= limit) {
+                return Err(err(&format!("doesn't fit in {} bits", bits)));
+            }
+            let value = if negative {
+                (!abs).overflowing_add(U256::one()).0
+            } else {
+                abs
+            };
+            Ok(Token::Int(value))
+        }
+        ParamType::Array(inner) => {
+            let values = value
+                .as_array()
+                .ok_or_else(|| err("expected a JSON array"))?;
+            Ok(Token::Array(tokenize_all(inner, values, path)?))
+        }
+        ParamType::FixedArray(inner, size) => {
+            let values = value
+                .as_array()
+                .ok_or_else(|| err("expected a JSON array"))?;
+            if values.len() != *size {
+                return Err(err(&format!(
+                    "expected {} items, got {}",
+                    size,
+                    values.len()
+                )));
+            }
+            Ok(Token::FixedArray(tokenize_all(inner, values, path)?))
+        }
+        ParamType::Tuple(types) => {
+            let values = value
+                .as_array()
+                .ok_or_else(|| err("expected a JSON array of the tuple fields"))?;
+            if values.len() != types.len() {
+                return Err(err(&format!(
+                    "expected {} fields, got {}",
+                    types.len(),
+                    values.len()
+                )));
+            }
+            let tokens = types
+                .iter()
+                .zip(values)
+                .enumerate()
+                .map(|(index, (param_type, value))| {
+                    tokenize(param_type, value, &format!("{}.{}", path, index))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Token::Tuple(tokens))
+        }
+    }
+}
+
+fn tokenize_all(param_type: &ParamType, values: &[Value], path: &str) -> Result<Vec<Token>> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| tokenize(param_type, value, &format!("{}[{}]", path, index)))
+        .collect()
+}
+
+fn hex_bytes(value: &Value) -> Option<Vec<u8>> {
+    let value = value.as_str()?;
+    hex::decode(strip_hex_prefix(value).ok()?).ok()
+}
+
+// JSON numbers, decimal strings or 0x hex strings
+fn unsigned(value: &Value) -> Option<U256> {
+    match value {
+        Value::Number(number) => number.as_u64().map(U256::from),
+        Value::String(value) => match strip_hex_prefix(value) {
+            Ok(digits) if !digits.is_empty() => U256::from_str_radix(digits, 16).ok(),
+            Ok(_) => None,
+            Err(_) if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) => {
+                U256::from_dec_str(value).ok()
+            }
+            Err(_) => None,
+        },
+        _ => None,
+    }
+}
+
+// Sign and absolute value of JSON numbers or decimal strings
+fn signed(value: &Value) -> Option<(bool, U256)> {
+    match value {
+        Value::Number(number) => {
+            let number = number.as_i64()?;
+            Some((number < 0, U256::from(number.unsigned_abs())))
+        }
+        Value::String(value) => {
+            let (negative, abs) = match value.strip_prefix('-') {
+                Some(abs) => (true, abs),
+                None => (false, value.as_str()),
+            };
+            Some((negative, unsigned(&Value::String(abs.to_string()))?))
+        }
+        _ => None,
+    }
+}
+
+/**
+ * Decimal text of an `int` token, ABI words being two's complement
+ */
+fn signed_text(value: &U256) -> String {
+    if value.bit(255) {
+        let abs = (!*value).overflowing_add(U256::one()).0;
+        format!("-{}", abs)
+    } else {
+        value.to_string()
+    }
+}
+
+/**
+ * JSON rendering of a token. Integers are decimal strings as they may not fit
+ * a JSON number, bytes and addresses 0x hex, arrays and tuples JSON arrays.
+ */
+pub fn token_json(token: &Token) -> Value {
+    match token {
+        Token::Address(address) => Value::String(format!("{:?}", address)),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+            Value::String(format!("0x{}", hex::encode(bytes)))
+        }
+        Token::Uint(value) => Value::String(value.to_string()),
+        Token::Int(value) => Value::String(signed_text(value)),
+        Token::Bool(value) => Value::Bool(*value),
+        Token::String(value) => Value::String(value.clone()),
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            Value::Array(tokens.iter().map(token_json).collect())
+        }
+    }
+}
+
+/**
+ * Text of a token: scalars as plain text, arrays and tuples as JSON
+ */
+pub fn token_text(token: &Token) -> String {
+    match token_json(token) {
+        Value::String(text) => text,
+        value => value.to_string(),
+    }
+}
+
+/**
+ * A named, typed value as returned to callers
+ */
+pub fn abi_value(name: &str, kind: &ParamType, token: &Token) -> DataLogParam {
+    DataLogParam {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        value: token_text(token),
+    }
+}
+
+/**
+ * JSON object of named values, unnamed ones are keyed by their position
+ */
+pub fn values_json<'a>(values: impl Iterator<Item = (&'a str, &'a Token)>) -> Value {
+    values_json_with(values, token_json)
+}
+
+/**
+ * Same as `values_json` with the tokens rendered by `render`
+ */
+pub fn values_json_with<'a>(
+    values: impl Iterator<Item = (&'a str, &'a Token)>,
+    render: fn(&Token) -> Value,
+) -> Value {
+    let object: Map<String, Value> = values
+        .enumerate()
+        .map(|(index, (name, token))| {
+            let name = match name {
+                "" => index.to_string(),
+                name => name.to_string(),
+            };
+            (name, render(token))
+        })
+        .collect();
+
+    Value::Object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(type_name: &str, value: &str) -> Result<Token> {
+        tokenize_arg(&Reader::read(type_name).unwrap(), value, "arg")
+    }
+
+    #[test]
+    fn aliases_are_canonical() {
+        assert_eq!(canonical_type("uint"), "uint256");
+        assert_eq!(
+            canonical_type("(uint, int[], bytes32)[2]"),
+            "(uint256,int256[],bytes32)[2]"
+        );
+        assert_eq!(canonical_type("uint8"), "uint8");
+    }
+
+    #[test]
+    fn types_are_parsed() {
+        assert_eq!(parse_type("uint"), Ok(ParamType::Uint(256)));
+        assert_eq!(
+            parse_type("(address,bytes32[2])[]"),
+            Ok(ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ParamType::Address,
+                ParamType::FixedArray(Box::new(ParamType::FixedBytes(32)), 2),
+            ]))))
+        );
+        for unknown in [
+            "uint7",
+            "int512",
+            "bytes33",
+            "bytes0",
+            "float",
+            "(uint256,foo)",
+            "",
+        ] {
+            assert!(
+                matches!(parse_type(unknown), Err(Error::InvalidArgument(_))),
+                "{:?} was accepted",
+                unknown
+            );
+        }
+    }
+
+    #[test]
+    fn scalars_are_encoded() {
+        assert_eq!(encode("bool", "true"), Ok(Token::Bool(true)));
+        assert_eq!(encode("uint8", "255"), Ok(Token::Uint(255.into())));
+        assert_eq!(encode("uint256", "0xff"), Ok(Token::Uint(255.into())));
+        assert_eq!(encode("int256", "-1"), Ok(Token::Int(U256::MAX)));
+        assert_eq!(encode("int8", "-128"), Ok(Token::Int(U256::MAX - 127)));
+        assert_eq!(
+            encode("bytes32", &format!("0x{}", "ab".repeat(32))),
+            Ok(Token::FixedBytes(vec![0xab; 32]))
+        );
+        assert_eq!(
+            encode("string", "hello"),
+            Ok(Token::String("hello".to_string()))
+        );
+    }
+
+    #[test]
+    fn arrays_and_tuples_use_json() {
+        assert_eq!(
+            encode("uint8[]", "[1, \"2\"]"),
+            Ok(Token::Array(vec![
+                Token::Uint(1.into()),
+                Token::Uint(2.into())
+            ]))
+        );
+        assert_eq!(
+            encode(
+                "(address,bool[2])",
+                r#"["0x00000000000000000000000000000000000000aa", [true, false]]"#
+            ),
+            Ok(Token::Tuple(vec![
+                Token::Address(H160::from_low_u64_be(0xaa)),
+                Token::FixedArray(vec![Token::Bool(true), Token::Bool(false)]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn tokens_render_as_json() {
+        let token = Token::Tuple(vec![
+            Token::Int(U256::MAX),
+            Token::Uint(U256::MAX),
+            Token::Array(vec![Token::Address(H160::from_low_u64_be(0xaa))]),
+            Token::FixedBytes(vec![0xab; 2]),
+        ]);
+
+        assert_eq!(
+            token_json(&token),
+            serde_json::json!([
+                "-1",
+                U256::MAX.to_string(),
+                ["0x00000000000000000000000000000000000000aa"],
+                "0xabab"
+            ])
+        );
+        assert_eq!(token_text(&Token::Int(5.into())), "5");
+        assert_eq!(
+            values_json(
+                [
+                    ("", &Token::Bool(true)),
+                    ("owner", &Token::String("a".into()))
+                ]
+                .into_iter()
+            ),
+            serde_json::json!({ "0": true, "owner": "a" })
+        );
+    }
+
+    #[test]
+    fn mismatches_name_the_value() {
+        for (type_name, value) in [
+            ("uint8", "256"),
+            ("int8", "128"),
+            ("uint256", "-1"),
+            ("bool", "yes"),
+            ("address", "0x1234"),
+            ("bytes32", "0x12"),
+            ("uint8[2]", "[1]"),
+            ("(uint256,bool)", "[1]"),
+            ("uint256[]", "1,2"),
+        ] {
+            assert!(
+                matches!(encode(type_name, value), Err(Error::InvalidArgument(_))),
+                "{} accepted {}",
+                type_name,
+                value
+            );
+        }
+
+        let err = encode("(uint256,bool[])", r#"[1, [true, 2]]"#).unwrap_err();
+        assert!(err.to_string().contains("arg.1[1]"), "{}", err);
+    }
+}
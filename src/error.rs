@@ -0,0 +1,246 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// JSON-RPC error codes nodes use for rate limiting ("limit exceeded")
+pub const RETRYABLE_RPC_CODES: [i64; 1] = [-32005];
+
+// JSON-RPC error code of calls the EVM reverted, nodes without it only say so in the message
+pub const EXECUTION_REVERTED_CODE: i64 = 3;
+
+// curl exit codes of connection level failures another try may not hit:
+// dns, connect, timeout, tls handshake, send/recv failures and empty replies
+pub const RETRYABLE_CURL_CODES: [i32; 8] = [5, 6, 7, 28, 35, 52, 55, 56];
+
+// curl exit codes of failures that happen before the request is sent:
+// dns, connect and tls handshake failures
+pub const UNSENT_CURL_CODES: [i32; 4] = [5, 6, 7, 35];
+
+// Every failure the service can report back to the caller
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // curl could not be run or produced no usable output
+    Transport(String),
+    // curl exited with a non-zero code, e.g. DNS, TLS or timeout failures
+    Curl {
+        exit_code: i32,
+        stderr: String,
+    },
+    // The endpoint answered with a non-2xx HTTP status
+    Http {
+        status: u16,
+        body: String,
+    },
+    // A response body exceeds the `max_response_bytes` of its endpoint, `size` is 0
+    // when curl aborted the download before it was received
+    ResponseTooLarge {
+        size: u64,
+        limit: u64,
+    },
+    // A url was rejected before being handed to curl
+    InvalidUrl(String),
+    // A service setting such as an endpoint config was rejected
+    InvalidConfig(String),
+    // An argument of an exported call was rejected before any request was sent
+    InvalidArgument(String),
+    // An endpoint serves another chain than the network it is registered for
+    ChainMismatch {
+        network: String,
+        expected: u64,
+        actual: u64,
+    },
+    // The node answered with a JSON-RPC error object
+    JsonRpc {
+        code: i64,
+        message: String,
+        data: String,
+    },
+    // The EVM reverted a call, `data` holds the hex revert data if the node returned it
+    Reverted {
+        code: i64,
+        message: String,
+        data: String,
+    },
+    // A file of the service, such as a spooled request body, could not be written
+    Io(String),
+    // A response body is not valid JSON or does not have the expected shape
+    MalformedJson(String),
+    // An ABI could not be loaded or does not describe the requested item
+    Abi(String),
+    // A hex string could not be decoded
+    Hex(String),
+    // Bytes could not be decoded into the requested values
+    Decoding(String),
+}
+
+impl Error {
+    /**
+     * Stable, machine readable category reported as `error_code`
+     */
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Transport(_) => "transport",
+            Error::Curl { .. } => "curl",
+            Error::Http { .. } => "http",
+            Error::ResponseTooLarge { .. } => "response_too_large",
+            Error::InvalidUrl(_) => "invalid_url",
+            Error::InvalidConfig(_) => "invalid_config",
+            Error::InvalidArgument(_) => "invalid_argument",
+            Error::ChainMismatch { .. } => "chain_mismatch",
+            Error::JsonRpc { .. } => "json_rpc",
+            Error::Reverted { .. } => "reverted",
+            Error::Io(_) => "io",
+            Error::MalformedJson(_) => "malformed_json",
+            Error::Abi(_) => "abi",
+            Error::Hex(_) => "hex",
+            Error::Decoding(_) => "decoding",
+        }
+    }
+
+    /**
+     * Tell reverts apart from other JSON-RPC errors of an eth_call or eth_estimateGas
+     */
+    pub fn into_revert(self) -> Self {
+        match self {
+            Error::JsonRpc {
+                code,
+                message,
+                data,
+            } if code == EXECUTION_REVERTED_CODE || message.contains("revert") => {
+                // Some nodes put a description rather than the revert data here
+                let is_hex = data
+                    .strip_prefix("0x")
+                    .is_some_and(|digits| hex::decode(digits).is_ok());
+                Error::Reverted {
+                    code,
+                    message,
+                    data: if is_hex { data } else { "".to_string() },
+                }
+            }
+            err => err,
+        }
+    }
+
+    /**
+     * Whether the same request may succeed when sent again or to another endpoint
+     */
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Transport(_) => true,
+            Error::Curl { exit_code, .. } => RETRYABLE_CURL_CODES.contains(exit_code),
+            Error::Http { status, .. } => *status == 429 || *status >= 500,
+            Error::JsonRpc { code, .. } => RETRYABLE_RPC_CODES.contains(code),
+            Error::MalformedJson(_)
+            | Error::Io(_)
+            | Error::ResponseTooLarge { .. }
+            | Error::InvalidUrl(_)
+            | Error::InvalidConfig(_)
+            | Error::InvalidArgument(_)
+            | Error::ChainMismatch { .. }
+            | Error::Reverted { .. }
+            | Error::Abi(_)
+            | Error::Hex(_)
+            | Error::Decoding(_) => false,
+        }
+    }
+
+    /**
+     * Whether the request surely never reached the node or was not processed by it,
+     * so even a request that changes state can be sent again
+     */
+    pub fn is_retryable_unsent(&self) -> bool {
+        match self {
+            Error::Curl { exit_code, .. } => UNSENT_CURL_CODES.contains(exit_code),
+            Error::Http { status, .. } => *status == 429,
+            Error::JsonRpc { code, .. } => RETRYABLE_RPC_CODES.contains(code),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(msg) => write!(f, "transport error: {}", msg),
+            Error::Curl { exit_code, stderr } => {
+                write!(f, "curl exited with code {}: {}", exit_code, stderr.trim())
+            }
+            Error::Http { status, body } => {
+                // Error pages can be large, the start is enough to identify them
+                let body: String = body.chars().take(200).collect();
+                write!(f, "http status {}: {}", status, body.trim())
+            }
+            Error::ResponseTooLarge { size: 0, limit } => {
+                write!(f, "response exceeds the {} bytes limit", limit)
+            }
+            Error::ResponseTooLarge { size, limit } => write!(
+                f,
+                "response of {} bytes exceeds the {} bytes limit",
+                size, limit
+            ),
+            Error::InvalidUrl(msg) => write!(f, "invalid url: {}", msg),
+            Error::InvalidConfig(msg) => write!(f, "invalid config: {}", msg),
+            Error::InvalidArgument(msg) => write!(f, "invalid argument: {}", msg),
+            Error::ChainMismatch {
+                network,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "network {} expects chain id {} but the endpoint serves chain {}",
+                network, expected, actual
+            ),
+            Error::JsonRpc {
+                code,
+                message,
+                data,
+            } if data.is_empty() => write!(f, "json-rpc error {}: {}", code, message),
+            Error::JsonRpc {
+                code,
+                message,
+                data,
+            } => write!(f, "json-rpc error {}: {} ({})", code, message, data),
+            Error::Reverted { message, data, .. } if data.is_empty() => {
+                write!(f, "reverted: {}", message)
+            }
+            Error::Reverted { message, data, .. } => write!(f, "reverted: {} ({})", message, data),
+            Error::Io(msg) => write!(f, "io error: {}", msg),
+            Error::MalformedJson(msg) => write!(f, "malformed json: {}", msg),
+            Error::Abi(msg) => write!(f, "abi error: {}", msg),
+            Error::Hex(msg) => write!(f, "invalid hex: {}", msg),
+            Error::Decoding(msg) => write!(f, "decoding error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::MalformedJson(err.to_string())
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(err: hex::FromHexError) -> Self {
+        Error::Hex(err.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(err: std::num::ParseIntError) -> Self {
+        Error::Hex(err.to_string())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(err: std::string::FromUtf8Error) -> Self {
+        Error::Decoding(err.to_string())
+    }
+}
+
+impl From<ethabi::Error> for Error {
+    fn from(err: ethabi::Error) -> Self {
+        Error::Abi(err.to_string())
+    }
+}
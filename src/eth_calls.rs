@@ -1,37 +1,85 @@
-use crate::curl_request_res;
-use crate::eth_contract::{decode_batch_logs, decode_logs};
+use crate::error::{Error, Result};
+use crate::eth_contract::decode_batch_logs;
 use crate::eth_utils::{
-    check_response_block_string, check_response_log_string, check_response_string,
-    check_response_transaction_string, get_nonce,
+    get_nonce, normalize_hex_bytes, normalize_hex_word, validate_reward_percentiles, BlockId,
 };
 use crate::fce_results::{
-    JsonRpcBlockResult, JsonRpcLogResult, JsonRpcResult, JsonRpcTransactionResult,
+    JsonRpcBlockResult, JsonRpcCallResult, JsonRpcFeeHistoryResult, JsonRpcLogResult,
+    JsonRpcReceiptResult, JsonRpcResult, JsonRpcTransactionResult, JsonRpcU64Result,
 };
-use crate::jsonrpc_helpers::Request;
-use crate::models::log_param::EventLogParamResult;
-use crate::types::TxCall;
+use crate::jsonrpc_helpers::{BatchRequest, Request};
+use crate::models::log_param::EventLogsResult;
+use crate::transport::{send_batch, send_request};
+use crate::types::{BatchCall, CallOverrides, CallRequest, LogFilter, TxCall};
 
 use jsonrpc_core as rpc;
 use marine_rs_sdk::marine;
+use serde::de::DeserializeOwned;
 use serde_json::json;
 
 pub fn serialize<T: serde::Serialize>(t: &T) -> rpc::Value {
     serde_json::to_value(t).expect("Types never fail to serialize.")
 }
 
-pub fn eth_call(url: String, tx: TxCall, tag: String) -> JsonRpcResult {
+/**
+ * Run `call` against the state at `block` without sending a transaction,
+ * `data` being raw calldata hex
+ */
+#[marine]
+pub fn eth_call(url: String, call: CallRequest, block: String) -> JsonRpcCallResult {
+    let call = block
+        .parse::<BlockId>()
+        .and_then(|block| Ok((TxCall::try_from(&call)?, block)));
+
+    match call {
+        Ok((tx, block)) => call_tx(url, tx, block, Vec::new()),
+        Err(err) => JsonRpcCallResult::from_result(Err(err), 0, 0),
+    }
+}
+
+/**
+ * Simulate `call` at `block` with accounts and block header fields replaced by `overrides`
+ */
+#[marine]
+pub fn eth_call_with_overrides(
+    url: String,
+    call: CallRequest,
+    block: String,
+    overrides: CallOverrides,
+) -> JsonRpcCallResult {
+    let call = block.parse::<BlockId>().and_then(|block| {
+        let tx = TxCall::try_from(&call)?;
+        Ok((tx, block, overrides.params()?))
+    });
+
+    match call {
+        Ok((tx, block, overrides)) => call_tx(url, tx, block, overrides),
+        Err(err) => JsonRpcCallResult::from_result(Err(err), 0, 0),
+    }
+}
+
+/**
+ * eth_call of `tx`, `overrides` being the params following the block
+ */
+pub fn call_tx(
+    url: String,
+    tx: TxCall,
+    block: BlockId,
+    overrides: Vec<rpc::Value>,
+) -> JsonRpcCallResult {
     let method = "eth_call".to_string();
 
     let tx_call_serial = serialize(&tx);
-    let tag_serial = serialize(&tag);
-    let params: rpc::Value = json!(vec![tx_call_serial, tag_serial]);
+    let mut params = vec![tx_call_serial, block.param()];
+    params.extend(overrides);
+    let params: rpc::Value = json!(params);
 
     let id = get_nonce();
 
-    let curl_args = Request::new(method, params, id).as_sys_string(&url);
-    let response = curl_request_res(curl_args).unwrap();
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
 
-    check_response_string(response, &id)
+    JsonRpcCallResult::from_result(response.result, id, response.attempts)
 }
 
 // pub fn eth_send_transaction(url: String, tx: TxCall) -> JsonRpcResult {
@@ -42,14 +90,14 @@ pub fn eth_call(url: String, tx: TxCall, tag: String) -> JsonRpcResult {
 
 //     let id = get_nonce();
 
-//     let curl_args = Request::new(method, params, id).as_sys_string(&url);
-//     let response = curl_request_res(curl_args).unwrap();
+//     let request = Request::new(method, params, id);
+//     let response = send_request(&url, &request);
 
-//     check_response_string(response, &id)
+//     JsonRpcResult::from_result(response.result, id, response.attempts)
 // }
 
 #[marine]
-pub fn eth_get_transaction_receipt(url: String, trans_hash: String) -> JsonRpcTransactionResult {
+pub fn eth_get_transaction_receipt(url: String, trans_hash: String) -> JsonRpcReceiptResult {
     let method = "eth_getTransactionReceipt".to_string();
 
     let trans_hash_serial = serialize(&trans_hash);
@@ -59,10 +107,80 @@ pub fn eth_get_transaction_receipt(url: String, trans_hash: String) -> JsonRpcTr
 
     let id = get_nonce();
 
-    let curl_args = Request::new(method, params, id).as_sys_string(&url);
-    let response = curl_request_res(curl_args).unwrap();
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
     log::info!("{:?}", response);
-    check_response_transaction_string(response, &id)
+    JsonRpcReceiptResult::from_result(response.result, id, response.attempts)
+}
+
+#[marine]
+pub fn eth_get_transaction_by_hash(url: String, trans_hash: String) -> JsonRpcTransactionResult {
+    let method = "eth_getTransactionByHash".to_string();
+
+    let trans_hash_serial = serialize(&trans_hash);
+    let params: rpc::Value = json!(vec![trans_hash_serial]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcTransactionResult::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Transaction at position `index` of a block given by number or tag
+ */
+#[marine]
+pub fn eth_get_transaction_by_block_number_and_index(
+    url: String,
+    block_in_hex: String,
+    index: u64,
+) -> JsonRpcTransactionResult {
+    let method = "eth_getTransactionByBlockNumberAndIndex".to_string();
+
+    let block_serial = match block_in_hex
+        .parse::<BlockId>()
+        .and_then(|block| block.number_param())
+    {
+        Ok(block) => block,
+        Err(err) => return JsonRpcTransactionResult::from_result(Err(err), 0, 0),
+    };
+    let index_serial = serialize(&format!("0x{:x}", index));
+    let params: rpc::Value = json!(vec![block_serial, index_serial]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcTransactionResult::from_result(response.result, id, response.attempts)
+}
+
+#[marine]
+pub fn eth_get_transaction_by_block_hash_and_index(
+    url: String,
+    block_hash: String,
+    index: u64,
+) -> JsonRpcTransactionResult {
+    let method = "eth_getTransactionByBlockHashAndIndex".to_string();
+
+    let block_hash_serial = match block_hash
+        .parse::<BlockId>()
+        .and_then(|block| block.hash_param())
+    {
+        Ok(block_hash) => block_hash,
+        Err(err) => return JsonRpcTransactionResult::from_result(Err(err), 0, 0),
+    };
+    let index_serial = serialize(&format!("0x{:x}", index));
+    let params: rpc::Value = json!(vec![block_hash_serial, index_serial]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcTransactionResult::from_result(response.result, id, response.attempts)
 }
 
 #[marine]
@@ -72,28 +190,79 @@ pub fn eth_get_latest_block_number(url: String) -> JsonRpcResult {
 
     let id = get_nonce();
 
-    let curl_args = Request::new(method, params, id).as_sys_string(&url);
-    log::info!("{}", curl_args.join(" "));
-    let response = curl_request_res(curl_args).unwrap();
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    log::info!("{:?}", response);
+    JsonRpcResult::from_result(response.result, id, response.attempts)
+}
+
+#[marine]
+pub fn eth_get_chain_id(url: String) -> JsonRpcResult {
+    let method = "eth_chainId".to_string();
+    let params: rpc::Value = json!([]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
 
-    log::info!("{}", response);
-    check_response_string(response, &id)
+    JsonRpcResult::from_result(response.result, id, response.attempts)
 }
 
+/**
+ * Block header with either full transactions (`hydrated`) or only their hashes
+ */
 #[marine]
-pub fn eth_get_block_by_number(url: String, block_in_hex: String) -> JsonRpcBlockResult {
+pub fn eth_get_block_by_number(
+    url: String,
+    block_in_hex: String,
+    hydrated: bool,
+) -> JsonRpcBlockResult {
     let method = "eth_getBlockByNumber".to_string();
 
-    let block_serial = serialize(&block_in_hex);
-    let is_hydrated_serial = serialize(&true);
+    let block_serial = match block_in_hex
+        .parse::<BlockId>()
+        .and_then(|block| block.number_param())
+    {
+        Ok(block) => block,
+        Err(err) => return JsonRpcBlockResult::from_result(Err(err), 0, 0),
+    };
+    let is_hydrated_serial = serialize(&hydrated);
     let params: rpc::Value = json!(vec![block_serial, is_hydrated_serial]);
 
     let id = get_nonce();
 
-    let curl_args = Request::new(method, params, id).as_sys_string(&url);
-    let response = curl_request_res(curl_args).unwrap();
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
 
-    check_response_block_string(response, &id)
+    JsonRpcBlockResult::from_result(response.result, id, response.attempts)
+}
+
+#[marine]
+pub fn eth_get_block_by_hash(
+    url: String,
+    block_hash: String,
+    hydrated: bool,
+) -> JsonRpcBlockResult {
+    let method = "eth_getBlockByHash".to_string();
+
+    let block_hash_serial = match block_hash
+        .parse::<BlockId>()
+        .and_then(|block| block.hash_param())
+    {
+        Ok(block_hash) => block_hash,
+        Err(err) => return JsonRpcBlockResult::from_result(Err(err), 0, 0),
+    };
+    let is_hydrated_serial = serialize(&hydrated);
+    let params: rpc::Value = json!(vec![block_hash_serial, is_hydrated_serial]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcBlockResult::from_result(response.result, id, response.attempts)
 }
 
 #[marine]
@@ -105,27 +274,198 @@ pub fn eth_send_raw_transaction(url: String, signed_tx: String) -> JsonRpcResult
 
     let id = get_nonce();
 
-    let curl_args = Request::new(method, params, id).as_sys_string(&url);
-    let response = curl_request_res(curl_args).unwrap();
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
 
-    check_response_string(response, &id)
+    JsonRpcResult::from_result(response.result, id, response.attempts)
 }
 
+/**
+ * Balance of `add` at `block`, a block number, hash or tag
+ */
 #[marine]
-pub fn eth_get_balance(url: String, add: String) -> JsonRpcResult {
+pub fn eth_get_balance(url: String, add: String, block: String) -> JsonRpcResult {
     let method = "eth_getBalance".to_string();
 
+    let block = match block.parse::<BlockId>() {
+        Ok(block) => block,
+        Err(err) => return JsonRpcResult::from_result(Err(err), 0, 0),
+    };
+    let add_serial = serialize(&add);
+    let params: rpc::Value = json!(vec![add_serial, block.param()]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    log::info!("{:?}", response);
+    JsonRpcResult::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Bytecode deployed at `add`, `0x` when it is not a contract
+ */
+#[marine]
+pub fn eth_get_code(url: String, add: String, block: String) -> JsonRpcResult {
+    let method = "eth_getCode".to_string();
+
+    let block = match block.parse::<BlockId>() {
+        Ok(block) => block,
+        Err(err) => return JsonRpcResult::from_result(Err(err), 0, 0),
+    };
+    let add_serial = serialize(&add);
+    let params: rpc::Value = json!(vec![add_serial, block.param()]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request::<String>(&url, &request);
+
+    let code = response.result.and_then(|code| normalize_hex_bytes(&code));
+    JsonRpcResult::from_result(code, id, response.attempts)
+}
+
+/**
+ * Storage slot `position` of `add` as a 32 byte hex word
+ */
+#[marine]
+pub fn eth_get_storage_at(
+    url: String,
+    add: String,
+    position: String,
+    block: String,
+) -> JsonRpcResult {
+    let method = "eth_getStorageAt".to_string();
+
+    let params = normalize_hex_word(&position).and_then(|position| {
+        let block = block.parse::<BlockId>()?;
+        Ok(json!(vec![
+            serialize(&add),
+            serialize(&position),
+            block.param()
+        ]))
+    });
+    let params: rpc::Value = match params {
+        Ok(params) => params,
+        Err(err) => return JsonRpcResult::from_result(Err(err), 0, 0),
+    };
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request::<String>(&url, &request);
+
+    let slot = response.result.and_then(|slot| normalize_hex_word(&slot));
+    JsonRpcResult::from_result(slot, id, response.attempts)
+}
+
+/**
+ * Number of transactions sent from `add`, the nonce of its next transaction
+ * when read at the `pending` block
+ */
+#[marine]
+pub fn eth_get_transaction_count(url: String, add: String, block: String) -> JsonRpcU64Result {
+    let method = "eth_getTransactionCount".to_string();
+
+    let block = match block.parse::<BlockId>() {
+        Ok(block) => block,
+        Err(err) => return JsonRpcU64Result::from_result(Err(err), 0, 0),
+    };
     let add_serial = serialize(&add);
-    let tag_serial = serialize(&"latest".to_string());
-    let params: rpc::Value = json!(vec![add_serial, tag_serial]);
+    let params: rpc::Value = json!(vec![add_serial, block.param()]);
 
     let id = get_nonce();
 
-    let curl_args = Request::new(method, params, id).as_sys_string(&url);
-    let response = curl_request_res(curl_args).unwrap();
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
 
-    log::info!("{}", response);
-    check_response_string(response, &id)
+    JsonRpcU64Result::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Gas `call` would use if it was sent as a transaction at `block`
+ */
+#[marine]
+pub fn eth_estimate_gas(url: String, call: CallRequest, block: String) -> JsonRpcU64Result {
+    let method = "eth_estimateGas".to_string();
+
+    let params = block.parse::<BlockId>().and_then(|block| {
+        let tx = TxCall::try_from(&call)?;
+        Ok(json!(vec![serialize(&tx), block.param()]))
+    });
+    let params: rpc::Value = match params {
+        Ok(params) => params,
+        Err(err) => return JsonRpcU64Result::from_result(Err(err), 0, 0),
+    };
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    let gas = response.result.map_err(Error::into_revert);
+    JsonRpcU64Result::from_result(gas, id, response.attempts)
+}
+
+#[marine]
+pub fn eth_gas_price(url: String) -> JsonRpcU64Result {
+    let method = "eth_gasPrice".to_string();
+    let params: rpc::Value = json!([]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcU64Result::from_result(response.result, id, response.attempts)
+}
+
+#[marine]
+pub fn eth_max_priority_fee_per_gas(url: String) -> JsonRpcU64Result {
+    let method = "eth_maxPriorityFeePerGas".to_string();
+    let params: rpc::Value = json!([]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcU64Result::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Base fees and gas usage of the `block_count` blocks up to `newest_block`, with the
+ * priority fees paid in each of them at `reward_percentiles`
+ */
+#[marine]
+pub fn eth_fee_history(
+    url: String,
+    block_count: u64,
+    newest_block: String,
+    reward_percentiles: Vec<f64>,
+) -> JsonRpcFeeHistoryResult {
+    let method = "eth_feeHistory".to_string();
+
+    let params = validate_reward_percentiles(&reward_percentiles).and_then(|_| {
+        let newest_block = newest_block.parse::<BlockId>()?.number_param()?;
+        Ok(json!(vec![
+            serialize(&format!("0x{:x}", block_count)),
+            newest_block,
+            serialize(&reward_percentiles),
+        ]))
+    });
+    let params: rpc::Value = match params {
+        Ok(params) => params,
+        Err(err) => return JsonRpcFeeHistoryResult::from_result(Err(err), 0, 0),
+    };
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcFeeHistoryResult::from_result(response.result, id, response.attempts)
 }
 
 #[marine]
@@ -136,24 +476,252 @@ pub fn eth_get_logs(
     end_block_in_hex: &str,
     address: &str,
     topics: Vec<String>,
-) -> Vec<EventLogParamResult> {
+    match_anonymous: bool,
+) -> EventLogsResult {
     let method = "eth_getLogs".to_string();
 
-    let filter = json!({
-        "fromBlock": start_block_in_hex,
-        "toBlock": end_block_in_hex,
-        "address": address,
-        "topics": topics
-    });
+    let filter = match log_filter(start_block_in_hex, end_block_in_hex, address, topics) {
+        Ok(filter) => filter,
+        Err(err) => return Err(err).into(),
+    };
+    let params: rpc::Value = json!(vec![filter]);
 
+    request_logs(&url, abi_url, method, params, match_anonymous)
+}
+
+/**
+ * Logs matching `filter`, decoded with the ABI at `abi_url`. With `match_anonymous`
+ * logs no event signature matches are tried against the anonymous events.
+ */
+#[marine]
+pub fn eth_get_logs_by_filter(
+    url: String,
+    abi_url: String,
+    filter: LogFilter,
+    match_anonymous: bool,
+) -> EventLogsResult {
+    let method = "eth_getLogs".to_string();
+
+    let filter = match filter.param() {
+        Ok(filter) => filter,
+        Err(err) => return Err(err).into(),
+    };
+    let params: rpc::Value = json!(vec![filter]);
+
+    request_logs(&url, abi_url, method, params, match_anonymous)
+}
+
+/**
+ * Logs matching `filter` as returned by the node, without decoding them
+ */
+#[marine]
+pub fn eth_get_raw_logs(url: String, filter: LogFilter) -> JsonRpcLogResult {
+    let method = "eth_getLogs".to_string();
+
+    let filter = match filter.param() {
+        Ok(filter) => filter,
+        Err(err) => return JsonRpcLogResult::from_result(Err(err), 0, 0),
+    };
     let params: rpc::Value = json!(vec![filter]);
 
     let id = get_nonce();
 
-    let curl_args = Request::new(method, params, id).as_sys_string(&url);
-    let response = curl_request_res(curl_args).unwrap();
+    let request = Request::new(method, params, id);
+    let response = send_request(&url, &request);
+
+    JsonRpcLogResult::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Filter object of eth_getLogs and eth_newFilter for one address and exact topics
+ */
+pub fn log_filter(
+    start_block_in_hex: &str,
+    end_block_in_hex: &str,
+    address: &str,
+    topics: Vec<String>,
+) -> Result<rpc::Value> {
+    let start_block = start_block_in_hex.parse::<BlockId>()?.number_param()?;
+    let end_block = end_block_in_hex.parse::<BlockId>()?.number_param()?;
+
+    Ok(json!({
+        "fromBlock": start_block,
+        "toBlock": end_block,
+        "address": address,
+        "topics": topics
+    }))
+}
+
+/**
+ * Send a request the node answers with logs, and decode them with the ABI at `abi_url`
+ */
+pub fn request_logs(
+    url: &str,
+    abi_url: String,
+    method: String,
+    params: rpc::Value,
+    match_anonymous: bool,
+) -> EventLogsResult {
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(url, &request);
+
+    let log_result = JsonRpcLogResult::from_result(response.result, id, response.attempts);
+    if !log_result.success {
+        return log_result.into();
+    }
+
+    let mut logs_result: EventLogsResult = Ok(decode_batch_logs(
+        abi_url,
+        log_result.result,
+        match_anonymous,
+    ))
+    .into();
+    logs_result.attempts = log_result.attempts;
+    logs_result
+}
+
+/**
+ * Send all calls in one batch request, results are returned in the order of `calls`
+ * along with the number of attempts the batch took
+ */
+pub fn batch_call<T: DeserializeOwned>(
+    url: &str,
+    calls: Vec<(String, rpc::Value)>,
+) -> (Vec<(u64, Result<T>)>, u32) {
+    let ids: Vec<u64> = calls.iter().map(|_| get_nonce()).collect();
+    if ids.is_empty() {
+        return (Vec::new(), 0);
+    }
+
+    let requests = calls
+        .into_iter()
+        .zip(ids.iter())
+        .map(|((method, params), id)| Request::new(method, params, *id))
+        .collect();
+    let batch = BatchRequest::new(requests);
+
+    let response = send_batch(url, &batch);
+
+    let results = match response.result {
+        Ok(results) => ids.into_iter().zip(results).collect(),
+        Err(err) => ids.into_iter().map(|id| (id, Err(err.clone()))).collect(),
+    };
+    (results, response.attempts)
+}
+
+#[marine]
+pub fn eth_batch_call(url: String, calls: Vec<BatchCall>) -> Vec<JsonRpcResult> {
+    let mut params = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (index, call) in calls.into_iter().enumerate() {
+        match serde_json::from_str::<rpc::Value>(&call.params) {
+            Ok(call_params) => params.push((call.method, call_params)),
+            Err(err) => invalid.push((index, err)),
+        }
+    }
+
+    let (results, attempts) = batch_call::<rpc::Value>(&url, params);
+    let mut results: Vec<JsonRpcResult> = results
+        .into_iter()
+        .map(|(id, result)| {
+            let result = result.map(|value| match value {
+                rpc::Value::String(value) => value,
+                value => value.to_string(),
+            });
+            JsonRpcResult::from_result(result, id, attempts)
+        })
+        .collect();
+
+    // Calls with unparsable params are never sent, they fail in place
+    for (index, err) in invalid {
+        results.insert(index, JsonRpcResult::from_result(Err(err.into()), 0, 0));
+    }
+
+    results
+}
+
+#[marine]
+pub fn eth_get_blocks_by_number(
+    url: String,
+    blocks_in_hex: Vec<String>,
+    hydrated: bool,
+) -> Vec<JsonRpcBlockResult> {
+    let mut calls = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (index, block) in blocks_in_hex.iter().enumerate() {
+        match block
+            .parse::<BlockId>()
+            .and_then(|block| block.number_param())
+        {
+            Ok(block) => {
+                let params = json!(vec![block, serialize(&hydrated)]);
+                calls.push(("eth_getBlockByNumber".to_string(), params));
+            }
+            Err(err) => invalid.push((index, err)),
+        }
+    }
+
+    let (results, attempts) = batch_call(&url, calls);
+    let mut results: Vec<JsonRpcBlockResult> = results
+        .into_iter()
+        .map(|(id, result)| JsonRpcBlockResult::from_result(result, id, attempts))
+        .collect();
+
+    // Invalid blocks are never requested, they fail in place
+    for (index, err) in invalid {
+        results.insert(index, JsonRpcBlockResult::from_result(Err(err), 0, 0));
+    }
+
+    results
+}
 
-    let log_result = check_response_log_string(response, &id);
+#[marine]
+pub fn eth_get_transaction_receipts(
+    url: String,
+    trans_hashes: Vec<String>,
+) -> Vec<JsonRpcReceiptResult> {
+    let calls = trans_hashes
+        .iter()
+        .map(|trans_hash| {
+            let params = json!(vec![serialize(trans_hash)]);
+            ("eth_getTransactionReceipt".to_string(), params)
+        })
+        .collect();
+
+    let (results, attempts) = batch_call(&url, calls);
+    results
+        .into_iter()
+        .map(|(id, result)| JsonRpcReceiptResult::from_result(result, id, attempts))
+        .collect()
+}
 
-    decode_batch_logs(abi_url, log_result.clone().result)
+#[marine]
+pub fn eth_get_balances(url: String, adds: Vec<String>, block: String) -> Vec<JsonRpcResult> {
+    let block = match block.parse::<BlockId>() {
+        Ok(block) => block,
+        Err(err) => {
+            return adds
+                .iter()
+                .map(|_| JsonRpcResult::from_result(Err(err.clone()), 0, 0))
+                .collect()
+        }
+    };
+
+    let calls = adds
+        .iter()
+        .map(|add| {
+            let params = json!(vec![serialize(add), block.param()]);
+            ("eth_getBalance".to_string(), params)
+        })
+        .collect();
+
+    let (results, attempts) = batch_call(&url, calls);
+    results
+        .into_iter()
+        .map(|(id, result)| JsonRpcResult::from_result(result, id, attempts))
+        .collect()
 }
@@ -1,16 +1,44 @@
 use std::str::FromStr;
 
 use crate::{
+    abi_values::{
+        abi_value, canonical_type, token_json, token_text, tokenize_arg, values_json,
+        values_json_with,
+    },
     curl_request_res,
-    eth_calls::eth_call,
+    error::{Error, Result},
+    eth_calls::call_tx,
+    eth_utils::{strip_hex_prefix, BlockId},
+    fce_results::{JsonRpcCallResult, StringResult},
+    models::contract_call::{ContractCallResult, RevertReason},
     models::log_param::{DataLogParam, EventLogParamResult},
     types::{TxCall, TxLog},
 };
-use serde_json::{Map, Value, Number};
-use ethabi::{Contract, RawLog, Token};
+use ethabi::{Contract, Event, Function, Log, ParamType, RawLog, Token};
 use ethereum_types::{H160, H256, U256};
 use marine_rs_sdk::marine;
+use serde_json::Value;
 
+// Selector of Error(string), the error of `require` and `revert("...")`
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+// Selector of Panic(uint256), raised by failed asserts and arithmetic errors
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+const PANIC_REASONS: [(u64, &str); 9] = [
+    (0x01, "assertion failed"),
+    (0x11, "arithmetic overflow or underflow"),
+    (0x12, "division or modulo by zero"),
+    (0x21, "invalid enum value"),
+    (0x22, "invalid storage byte array"),
+    (0x31, "pop on an empty array"),
+    (0x32, "array index out of bounds"),
+    (0x41, "out of memory"),
+    (0x51, "call to an uninitialized function"),
+];
+
+// An argument of `method_name`, `value_type` may be left empty to take the type
+// from the ABI. Arrays and tuples are written as JSON arrays.
 #[marine]
 #[derive(Debug)]
 pub struct TxParam {
@@ -18,6 +46,76 @@ pub struct TxParam {
     value: String,
 }
 
+/**
+ * Download and parse the contract ABI
+ */
+fn fetch_abi(abi_url: String) -> Result<Contract> {
+    let response = curl_request_res(Vec::new(), &abi_url)?.into_body()?;
+    Ok(Contract::load(response.as_bytes())?)
+}
+
+/**
+ * The overload of `method_name` taking `tx_params`: same number of arguments
+ * and the same types wherever `value_type` is set. A function without overloads
+ * also takes "uint" and "int" for any uintN and intN, as callers used to.
+ */
+fn select_function<'a>(
+    contract: &'a Contract,
+    method_name: &str,
+    tx_params: &[TxParam],
+) -> Result<&'a Function> {
+    let overloads = contract.functions_by_name(method_name)?;
+    let is_overloaded = overloads.len() > 1;
+
+    overloads
+        .iter()
+        .find(|func| {
+            func.inputs.len() == tx_params.len()
+                && func.inputs.iter().zip(tx_params).all(|(input, param)| {
+                    let loose_match = match (param.value_type.trim(), &input.kind) {
+                        ("", _) => true,
+                        ("uint", ParamType::Uint(_)) | ("int", ParamType::Int(_)) => !is_overloaded,
+                        _ => false,
+                    };
+                    loose_match || canonical_type(&param.value_type) == input.kind.to_string()
+                })
+        })
+        .ok_or_else(|| {
+            let signatures: Vec<String> = overloads.iter().map(|func| func.signature()).collect();
+            let given: Vec<&str> = tx_params
+                .iter()
+                .map(|param| match param.value_type.as_str() {
+                    "" => "_",
+                    value_type => value_type,
+                })
+                .collect();
+            Error::InvalidArgument(format!(
+                "no overload of {} takes ({}), the ABI has {}",
+                method_name,
+                given.join(","),
+                signatures.join(", ")
+            ))
+        })
+}
+
+fn encode_params(func: &Function, tx_params: Vec<TxParam>) -> Result<Vec<Token>> {
+    func.inputs
+        .iter()
+        .zip(tx_params)
+        .enumerate()
+        .map(|(index, (input, param))| {
+            let path = match input.name.as_str() {
+                "" => format!("argument {}", index),
+                name => name.to_string(),
+            };
+            tokenize_arg(&input.kind, &param.value, &path)
+        })
+        .collect()
+}
+
+/**
+ * Call a view function and decode what it returns, or why it reverted, with its ABI
+ */
 #[marine]
 pub fn contract_view_call(
     node_url: String,
@@ -25,57 +123,221 @@ pub fn contract_view_call(
     method_name: String,
     contract_address: String,
     tx_params: Vec<TxParam>,
-) -> String {
-    let args = vec![format!(r#"{}"#, abi_url)];
-    let response = curl_request_res(args).unwrap();
-    let contract = Contract::load(response.as_bytes()).unwrap();
-    let func = contract.function(&method_name).unwrap();
-
-    let tokens: Vec<Token> = tx_params
-        .into_iter()
-        .map(|param| match param.value_type.as_ref() {
-            "address" => Token::Address(H160::from_str(&param.value).unwrap()),
-            "uint" => Token::Uint(U256::from_dec_str(&param.value).unwrap()),
-            _ => Token::String(param.value),
-        })
-        .collect();
+    block: String,
+) -> ContractCallResult {
+    let block = match block.parse::<BlockId>() {
+        Ok(block) => block,
+        Err(err) => return ContractCallResult::from_err(err),
+    };
+    let (contract, func, params) =
+        match build_view_call(abi_url, method_name, contract_address, tx_params) {
+            Ok(call) => call,
+            Err(err) => return ContractCallResult::from_err(err),
+        };
+
+    let call = call_tx(node_url, params, block, Vec::new());
+    decode_call(call, &contract, &func)
+}
+
+fn build_view_call(
+    abi_url: String,
+    method_name: String,
+    contract_address: String,
+    tx_params: Vec<TxParam>,
+) -> Result<(Contract, Function, TxCall)> {
+    let contract = fetch_abi(abi_url)?;
+    let func = select_function(&contract, &method_name, &tx_params)?.clone();
+
+    let tokens = encode_params(&func, tx_params)?;
 
-    let data_in_bytes = func.encode_input(tokens.as_slice()).unwrap();
+    let data_in_bytes = func.encode_input(tokens.as_slice())?;
 
-    let params = TxCall {
-        to: Some(H160::from_str(&contract_address).unwrap()),
+    let tx = TxCall {
+        to: Some(H160::from_str(&contract_address).map_err(|err| Error::Hex(err.to_string()))?),
         data: Some(data_in_bytes.into()),
         ..Default::default()
     };
+    Ok((contract, func, tx))
+}
+
+/**
+ * Decode the return data of a call to `func`, or its revert data
+ */
+fn decode_call(
+    call: JsonRpcCallResult,
+    contract: &Contract,
+    func: &Function,
+) -> ContractCallResult {
+    let mut result = ContractCallResult {
+        result: call.result,
+        data: Value::Null.to_string(),
+        reverted: call.reverted,
+        success: call.success,
+        error_code: call.error_code,
+        error_message: call.error_message,
+        rpc_error: call.rpc_error,
+        attempts: call.attempts,
+        id: call.id,
+        ..Default::default()
+    };
+
+    if call.reverted {
+        result.revert = decode_revert(contract, &call.revert_data);
+        if !result.revert.message.is_empty() {
+            result.error_message = format!("reverted: {}", result.revert.message);
+        }
+        return result;
+    }
+    if !call.success {
+        return result;
+    }
 
-    let response = eth_call(node_url, params, "latest".into()).result;
-    response
+    let outputs = strip_hex_prefix(&result.result)
+        .and_then(|output| Ok(hex::decode(output)?))
+        .and_then(|output| Ok(func.decode_output(&output)?));
+    match outputs {
+        Ok(tokens) => {
+            result.outputs = func
+                .outputs
+                .iter()
+                .zip(tokens.iter())
+                .map(|(output, token)| abi_value(&output.name, &output.kind, token))
+                .collect();
+            result.data = values_json(
+                func.outputs
+                    .iter()
+                    .map(|output| output.name.as_str())
+                    .zip(tokens.iter()),
+            )
+            .to_string();
+        }
+        Err(err) => {
+            result.success = false;
+            result.error_code = err.code().to_string();
+            result.error_message = format!("return data of {}: {}", func.name, err);
+        }
+    }
+
+    result
 }
 
 /**
- * Decode logs individually
+ * Decode revert data as Error(string), Panic(uint256) or one of the custom errors of the ABI
  */
-#[marine]
-pub fn decode_logs(abi_url: String, tx_log: TxLog) -> EventLogParamResult {
-    let args = vec![format!(r#"{}"#, abi_url)];
-    let response = curl_request_res(args).unwrap();
-    let contract = Contract::load(response.as_bytes()).unwrap();
+fn decode_revert(contract: &Contract, revert_data: &str) -> RevertReason {
+    let mut reason = RevertReason {
+        kind: "unknown".to_string(),
+        data: revert_data.to_string(),
+        ..Default::default()
+    };
 
-    decode_log(contract, tx_log)
+    let data = match strip_hex_prefix(revert_data).map(hex::decode) {
+        Ok(Ok(data)) if data.len() >= 4 => data,
+        _ => return reason,
+    };
+    let (selector, args) = data.split_at(4);
+
+    if selector == ERROR_SELECTOR {
+        if let Ok(tokens) = ethabi::decode(&[ParamType::String], args) {
+            reason.kind = "error".to_string();
+            reason.name = "Error".to_string();
+            reason.message = token_text(&tokens[0]);
+            reason.params = vec![abi_value("message", &ParamType::String, &tokens[0])];
+        }
+        return reason;
+    }
+
+    if selector == PANIC_SELECTOR {
+        if let Ok(tokens) = ethabi::decode(&[ParamType::Uint(256)], args) {
+            let code = tokens[0].clone().into_uint().unwrap_or_default();
+            let description = PANIC_REASONS
+                .iter()
+                .find(|(panic_code, _)| U256::from(*panic_code) == code)
+                .map(|(_, description)| *description)
+                .unwrap_or("unknown panic");
+            reason.kind = "panic".to_string();
+            reason.name = "Panic".to_string();
+            reason.message = format!("{} (0x{:x})", description, code);
+            reason.params = vec![abi_value("code", &ParamType::Uint(256), &tokens[0])];
+        }
+        return reason;
+    }
+
+    for error in contract.errors() {
+        if error.signature()[..4] != *selector {
+            continue;
+        }
+        if let Ok(tokens) = error.decode(args) {
+            reason.kind = "custom".to_string();
+            reason.name = error.name.clone();
+            reason.params = error
+                .inputs
+                .iter()
+                .zip(tokens.iter())
+                .map(|(input, token)| abi_value(&input.name, &input.kind, token))
+                .collect();
+            reason.message = format!(
+                "{}{}",
+                error.name,
+                values_json(
+                    error
+                        .inputs
+                        .iter()
+                        .map(|input| input.name.as_str())
+                        .zip(tokens.iter())
+                )
+            );
+            return reason;
+        }
+    }
+
+    reason
+}
+
+/**
+ * Decode logs individually. Logs are matched to events by their topic0 signature,
+ * with `match_anonymous` those no signature matches are also tried against the
+ * anonymous events of the ABI, the first one their topics and data decode as wins.
+ */
+#[marine]
+pub fn decode_logs(abi_url: String, tx_log: TxLog, match_anonymous: bool) -> EventLogParamResult {
+    match fetch_abi(abi_url) {
+        Ok(contract) => decode_log(&contract, tx_log, match_anonymous),
+        Err(err) => EventLogParamResult::from_err(err, tx_log),
+    }
 }
 
 /**
- * Decode logs in batches
+ * Decode logs in batches, when the ABI is unavailable every log is returned
+ * failed but with its raw log
  */
-pub fn decode_batch_logs(abi_url: String, tx_logs: Vec<TxLog>) -> Vec<EventLogParamResult> {
-    let args = vec![format!(r#"{}"#, abi_url)];
-    let response = curl_request_res(args).unwrap();
-    let contract = Contract::load(response.as_bytes()).unwrap();
+pub fn decode_batch_logs(
+    abi_url: String,
+    tx_logs: Vec<TxLog>,
+    match_anonymous: bool,
+) -> Vec<EventLogParamResult> {
+    decode_logs_with(fetch_abi(abi_url), tx_logs, match_anonymous)
+}
+
+fn decode_logs_with(
+    contract: Result<Contract>,
+    tx_logs: Vec<TxLog>,
+    match_anonymous: bool,
+) -> Vec<EventLogParamResult> {
+    let contract = match contract {
+        Ok(contract) => contract,
+        Err(err) => {
+            return tx_logs
+                .into_iter()
+                .map(|tx_log| EventLogParamResult::from_err(err.clone(), tx_log))
+                .collect()
+        }
+    };
 
     let mut data_events: Vec<EventLogParamResult> = Vec::new();
 
     for tx_log in tx_logs {
-        data_events.push(decode_log(contract.clone(), tx_log));
+        data_events.push(decode_log(&contract, tx_log, match_anonymous));
     }
 
     data_events
@@ -84,121 +346,477 @@ pub fn decode_batch_logs(abi_url: String, tx_logs: Vec<TxLog>) -> Vec<EventLogPa
 /**
  * Decode logs from topics and data
  */
-fn decode_log(contract: Contract, tx_log: TxLog ) -> EventLogParamResult {
+fn decode_log(contract: &Contract, tx_log: TxLog, match_anonymous: bool) -> EventLogParamResult {
+    match parse_log(contract, &tx_log, match_anonymous) {
+        Ok(result) => result,
+        Err(err) => EventLogParamResult::from_err(err, tx_log),
+    }
+}
+
+fn parse_log(
+    contract: &Contract,
+    tx_log: &TxLog,
+    match_anonymous: bool,
+) -> Result<EventLogParamResult> {
     let mut logs_h256: Vec<H256> = Vec::new();
 
-    for topic in tx_log.topics.clone() {
-        logs_h256.push(H256::from_str(&topic).unwrap())
+    for topic in tx_log.topics.iter() {
+        logs_h256.push(H256::from_str(topic).map_err(|err| Error::Hex(err.to_string()))?)
     }
+    let data = hex::decode(strip_hex_prefix(&tx_log.data)?)?;
 
-    let mut logs: Vec<DataLogParam> = Vec::new();
-
-    let event_name = logs_h256.clone()[0];
+    // Overloads share a name but not a signature, while ABIs merged from several
+    // contracts can hold one signature with different indexed inputs
+    let mut candidates: Vec<&Event> = match logs_h256.first() {
+        Some(topic) => contract
+            .events()
+            .filter(|event| !event.anonymous && event.signature() == *topic)
+            .collect(),
+        None => Vec::new(),
+    };
+    if candidates.is_empty() && match_anonymous {
+        candidates = contract
+            .events()
+            .filter(|event| {
+                event.anonymous
+                    && event.inputs.iter().filter(|input| input.indexed).count() == logs_h256.len()
+            })
+            .collect();
+    }
 
-    for (_, event) in contract.events {
-        if event_name == event[0].signature() {
-            let raw_log = RawLog {
-                topics: logs_h256.clone(),
-                data: hex::decode(&tx_log.data.clone()[2..]).unwrap(),
-            };
+    let mut last_err = None;
+    for event in candidates {
+        let raw_log = RawLog {
+            topics: logs_h256.clone(),
+            data: data.clone(),
+        };
 
-            let log = event[0].parse_log(raw_log).unwrap();
-            let mut data = Map::new();
-
-            for token in log.params {
-                match token.value.clone() {
-                    Token::Uint(value) => {
-                      logs.push(DataLogParam {
-                        name: token.name.clone(),
-                        kind: "uint".to_string(),
-                        value: value.clone().to_string(),
-                      });
-                      let json_number = Number::from_str(value.clone().to_string().as_str()).unwrap();
-                      data.insert(token.name.clone().to_string(), Value::Number(json_number));
-                    },
-                    Token::Address(address) => {
-                      logs.push(DataLogParam {
-                        name: token.name.clone(),
-                        kind: "address".to_string(),
-                        value: format!("0x{}", hex::encode(address).to_string()),
-                      });
-                      let str_address = format!("0x{}", hex::encode(address).to_string());
-                      data.insert(token.name.clone().to_string(), Value::String(str_address));
-                    },
-                    Token::Int(value) => {
-                      logs.push(DataLogParam {
-                        name: token.name.clone(),
-                        kind: "int".to_string(),
-                        value: value.clone().to_string(),
-                      });
-                    },
-                    Token::Bool(value) => {
-                      logs.push(DataLogParam {
-                        name: token.name.clone(),
-                        kind: "bool".to_string(),
-                        value: value.clone().to_string(),
-                      });
-                      data.insert(token.name.clone().to_string(), Value::Bool(value.clone()));
-                    },
-                    Token::Bytes(value) => {
-                      logs.push(DataLogParam {
-                        name: token.name.clone(),
-                        kind: "bytes".to_string(),
-                        value: hex::encode(value.clone()).to_string(),
-                      });
-                      let str_bytes = hex::encode(value.clone()).to_string();
-                      data.insert(token.name.clone().to_string(), Value::String(str_bytes));
-                    },
-                    Token::String(value) => {
-                      logs.push(DataLogParam {
-                        name: token.name.clone(),
-                        kind: "string".to_string(),
-                        value: value.clone().to_string(),
-                      });
-                      data.insert(token.name.clone().to_string(), Value::String(value.clone().to_string()));
-                    },
-                    _ => {
-                        log::info!("Other token: {:?}", token.value.clone());
-                    }
-                }
-            }
-
-            return EventLogParamResult {
-                event_name: event[0].clone().name,
-                params: logs,
-                success: true,
-                error_msg: "".to_string(),
-                data: Value::Object(data).to_string(),
-                block_number: tx_log.block_number,
-                transaction_hash: tx_log.transaction_hash,
-            };
+        match event.parse_log(raw_log) {
+            Ok(log) => return Ok(event_result(event, log, tx_log)),
+            Err(err) => last_err = Some(Error::Decoding(err.to_string())),
         }
     }
 
-    return EventLogParamResult {
-        event_name: "".to_string(),
-        params: Vec::new(),
-        success: false,
-        error_msg: "".to_string(),
-        data: Value::Null.to_string(),
-        block_number: 0,
-        transaction_hash: "".to_string(),
+    Err(last_err.unwrap_or_else(|| match logs_h256.first() {
+        Some(topic) => Error::Abi(format!("no event in the ABI matches topic {:?}", topic)),
+        None => Error::Abi("log has no topics and no anonymous event matches it".to_string()),
+    }))
+}
+
+fn event_result(event: &Event, log: Log, tx_log: &TxLog) -> EventLogParamResult {
+    // ethabi returns the params in the order of the event inputs
+    let logs: Vec<DataLogParam> = event
+        .inputs
+        .iter()
+        .zip(log.params.iter())
+        .map(|(input, param)| event_value(&param.name, &input.kind, &param.value))
+        .collect();
+    let data = values_json_with(
+        log.params
+            .iter()
+            .map(|param| (param.name.as_str(), &param.value)),
+        event_json,
+    );
+
+    EventLogParamResult {
+        event_name: event.name.clone(),
+        params: logs,
+        success: true,
+        error_code: "".to_string(),
+        error_message: "".to_string(),
+        data: data.to_string(),
+        block_number: tx_log.block_number,
+        transaction_hash: tx_log.transaction_hash.clone(),
+        log: tx_log.clone(),
+    }
+}
+
+/**
+ * An event param rendered as events always were, integers of kind "uint" or "int".
+ * The value is the text of `event_json`.
+ */
+fn event_value(name: &str, kind: &ParamType, token: &Token) -> DataLogParam {
+    let value = abi_value(name, kind, token);
+    let kind = match token {
+        Token::Uint(_) => "uint".to_string(),
+        Token::Int(_) => "int".to_string(),
+        _ => value.kind,
+    };
+    let text = match event_json(token) {
+        Value::String(text) => text,
+        json => json.to_string(),
     };
+
+    DataLogParam {
+        kind,
+        value: text,
+        ..value
+    }
+}
+
+/**
+ * JSON rendering of an event token: `bytes` are hex without 0x as events always
+ * had them, at any depth of arrays and tuples. Everything else, bytesN included,
+ * is rendered like call outputs.
+ */
+fn event_json(token: &Token) -> Value {
+    match token {
+        Token::Bytes(bytes) => Value::String(hex::encode(bytes)),
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            Value::Array(tokens.iter().map(event_json).collect())
+        }
+        token => token_json(token),
+    }
 }
 
 #[marine]
-pub fn decode_input_to_get_method_name(abi_url: String, input: String) -> String {
-    let args = vec![format!(r#"{}"#, abi_url)];
-    let response = curl_request_res(args).unwrap();
-    let contract = Contract::load(response.as_bytes()).unwrap();
+pub fn decode_input_to_get_method_name(abi_url: String, input: String) -> StringResult {
+    get_method_name(abi_url, &input).into()
+}
 
-    let input_bytes = hex::decode(&input[2..]).unwrap();
+fn get_method_name(abi_url: String, input: &str) -> Result<String> {
+    let contract = fetch_abi(abi_url)?;
+
+    let input_bytes = hex::decode(strip_hex_prefix(input)?)?;
+    let selector = input_bytes
+        .get(0..4)
+        .ok_or_else(|| Error::Decoding("input is shorter than a 4 byte selector".to_string()))?;
 
     for (name, function) in contract.functions {
-        if &input_bytes[0..4] == function[0].short_signature() {
-            return name;
+        if selector == function[0].short_signature() {
+            return Ok(name);
         }
     }
 
-    return "".to_string();
+    Err(Error::Abi(
+        "no function in the ABI matches the input selector".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABI: &str = r#"[{"type":"event","name":"Transfer","anonymous":false,"inputs":[
+        {"name":"from","type":"address","indexed":true},
+        {"name":"to","type":"address","indexed":true},
+        {"name":"value","type":"uint256","indexed":false}]}]"#;
+
+    #[test]
+    fn reverts_are_decoded() {
+        let abi = r#"[{"type":"error","name":"Unauthorized","inputs":[
+            {"name":"caller","type":"address"}]}]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+
+        let data = format!(
+            "0x{}{}",
+            hex::encode(ERROR_SELECTOR),
+            hex::encode(ethabi::encode(&[Token::String("not owner".into())]))
+        );
+        let reason = decode_revert(&contract, &data);
+        assert_eq!(
+            (reason.kind.as_str(), reason.message.as_str()),
+            ("error", "not owner")
+        );
+
+        let data = format!(
+            "0x{}{}",
+            hex::encode(PANIC_SELECTOR),
+            hex::encode(ethabi::encode(&[Token::Uint(0x11.into())]))
+        );
+        let reason = decode_revert(&contract, &data);
+        assert_eq!(reason.kind, "panic");
+        assert_eq!(reason.message, "arithmetic overflow or underflow (0x11)");
+
+        let error = contract.error("Unauthorized").unwrap();
+        let caller = Token::Address(H160::from_low_u64_be(0xaa));
+        let data = format!("0x{}", hex::encode(error.encode(&[caller]).unwrap()));
+        let reason = decode_revert(&contract, &data);
+        assert_eq!(
+            (reason.kind.as_str(), reason.name.as_str()),
+            ("custom", "Unauthorized")
+        );
+        assert_eq!(
+            reason.params[0].value,
+            "0x00000000000000000000000000000000000000aa"
+        );
+
+        assert_eq!(decode_revert(&contract, "0x12345678").kind, "unknown");
+    }
+
+    #[test]
+    fn outputs_are_named_and_typed() {
+        let abi = r#"[{"type":"function","name":"info","stateMutability":"view","inputs":[],
+            "outputs":[{"name":"owner","type":"address"},{"name":"","type":"int8"}]}]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let func = contract.function("info").unwrap();
+        let output = ethabi::encode(&[
+            Token::Address(H160::from_low_u64_be(0xaa)),
+            Token::Int(U256::MAX),
+        ]);
+        let call = JsonRpcCallResult::from_result(Ok(format!("0x{}", hex::encode(output))), 1, 1);
+
+        let result = decode_call(call, &contract, func);
+
+        assert!(result.success);
+        assert_eq!(result.outputs[1].kind, "int8");
+        assert_eq!(result.outputs[1].value, "-1");
+        assert_eq!(
+            result.data,
+            r#"{"1":"-1","owner":"0x00000000000000000000000000000000000000aa"}"#
+        );
+    }
+
+    #[test]
+    fn overload_is_selected_by_arguments() {
+        let abi = r#"[
+            {"type":"function","name":"balanceOf","stateMutability":"view","outputs":[],
+             "inputs":[{"name":"owner","type":"address"}]},
+            {"type":"function","name":"balanceOf","stateMutability":"view","outputs":[],
+             "inputs":[{"name":"owner","type":"address"},{"name":"id","type":"uint256"}]}
+        ]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let param = |value_type: &str, value: &str| TxParam {
+            value_type: value_type.to_string(),
+            value: value.to_string(),
+        };
+
+        let params = [
+            param("", "0x00000000000000000000000000000000000000aa"),
+            param("uint", "7"),
+        ];
+        let func = select_function(&contract, "balanceOf", &params).unwrap();
+        assert_eq!(func.signature(), "balanceOf(address,uint256)");
+
+        let params = [param("bool", "true")];
+        assert!(matches!(
+            select_function(&contract, "balanceOf", &params),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn single_function_takes_its_types_from_the_abi() {
+        let abi = r#"[{"type":"function","name":"setFee","stateMutability":"view","outputs":[],
+            "inputs":[{"name":"fee","type":"uint8"},{"name":"cap","type":"uint128"}]}]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let params = vec![
+            TxParam {
+                value_type: "uint".to_string(),
+                value: "3".to_string(),
+            },
+            TxParam {
+                value_type: "uint".to_string(),
+                value: "1000".to_string(),
+            },
+        ];
+
+        assert!(matches!(
+            select_function(&contract, "setFee", &params[..1]),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        let func = select_function(&contract, "setFee", &params).unwrap();
+        assert_eq!(func.signature(), "setFee(uint8,uint128)");
+        assert_eq!(
+            encode_params(func, params).unwrap(),
+            [Token::Uint(3.into()), Token::Uint(1000.into())]
+        );
+    }
+
+    #[test]
+    fn single_function_rejects_other_explicit_types() {
+        let abi = r#"[{"type":"function","name":"setFee","stateMutability":"view","outputs":[],
+            "inputs":[{"name":"fee","type":"uint256"}]}]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let params = vec![TxParam {
+            value_type: "address".to_string(),
+            value: "0x0000000000000000000000000000000000000001".to_string(),
+        }];
+
+        assert!(matches!(
+            select_function(&contract, "setFee", &params),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_events_keep_the_raw_log() {
+        let contract = Contract::load(ABI.as_bytes()).unwrap();
+        let tx_log = TxLog {
+            address: "0x00000000000000000000000000000000000000aa".to_string(),
+            topics: vec![format!("0x{}", "11".repeat(32))],
+            data: "0x".to_string(),
+            log_index: 4,
+            ..Default::default()
+        };
+
+        let decoded = decode_log(&contract, tx_log, false);
+
+        assert!(!decoded.success);
+        assert_eq!(decoded.error_code, "abi");
+        assert_eq!(
+            decoded.log.address,
+            "0x00000000000000000000000000000000000000aa"
+        );
+        assert_eq!(decoded.log.log_index, 4);
+        assert_eq!(decoded.log.topics.len(), 1);
+    }
+
+    #[test]
+    fn event_values_of_every_kind_are_decoded() {
+        let abi = r#"[{"type":"event","name":"Swap","anonymous":false,"inputs":[
+            {"name":"sender","type":"address","indexed":true},
+            {"name":"amount0","type":"int256","indexed":false},
+            {"name":"ids","type":"uint256[]","indexed":false},
+            {"name":"pool","type":"tuple","indexed":false,"components":[
+                {"name":"id","type":"bytes32"},{"name":"active","type":"bool"},
+                {"name":"salt","type":"bytes"}]},
+            {"name":"memo","type":"bytes","indexed":false}]}]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let event = contract.event("Swap").unwrap();
+
+        let data = ethabi::encode(&[
+            Token::Int(U256::MAX - 41),
+            Token::Array(vec![Token::Uint(U256::MAX), Token::Uint(1.into())]),
+            Token::Tuple(vec![
+                Token::FixedBytes(vec![0xab; 32]),
+                Token::Bool(true),
+                Token::Bytes(vec![0xbe, 0xef]),
+            ]),
+            Token::Bytes(vec![0xca, 0xfe]),
+        ]);
+        let tx_log = TxLog {
+            topics: vec![
+                format!("{:?}", event.signature()),
+                format!("0x{}{}", "00".repeat(12), "aa".repeat(20)),
+            ],
+            data: format!("0x{}", hex::encode(data)),
+            ..Default::default()
+        };
+
+        let decoded = decode_log(&contract, tx_log, false);
+        assert!(decoded.success, "{}", decoded.error_message);
+
+        let kinds: Vec<&str> = decoded
+            .params
+            .iter()
+            .map(|param| param.kind.as_str())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                "address",
+                "int",
+                "uint256[]",
+                "(bytes32,bool,bytes)",
+                "bytes"
+            ]
+        );
+        assert_eq!(decoded.params[1].value, "-42");
+        assert_eq!(
+            decoded.params[3].value,
+            format!(r#"["0x{}",true,"beef"]"#, "ab".repeat(32))
+        );
+        assert_eq!(decoded.params[4].value, "cafe");
+
+        let data: Value = serde_json::from_str(&decoded.data).unwrap();
+        assert_eq!(data["amount0"], "-42");
+        assert_eq!(data["ids"][0], U256::MAX.to_string());
+        assert_eq!(data["pool"][0], format!("0x{}", "ab".repeat(32)));
+        assert_eq!(data["pool"][1], true);
+        assert_eq!(data["pool"][2], "beef");
+        assert_eq!(data["memo"], "cafe");
+    }
+
+    #[test]
+    fn every_overload_of_an_event_is_matched() {
+        // ERC-20 and ERC-721 Transfer share a signature but not their indexed inputs
+        let abi = r#"[{"type":"event","name":"Transfer","anonymous":false,"inputs":[
+            {"name":"from","type":"address","indexed":true},
+            {"name":"to","type":"address","indexed":true},
+            {"name":"value","type":"uint256","indexed":false}]},
+            {"type":"event","name":"Transfer","anonymous":false,"inputs":[
+            {"name":"from","type":"address","indexed":true},
+            {"name":"to","type":"address","indexed":true},
+            {"name":"tokenId","type":"uint256","indexed":true}]},
+            {"type":"event","name":"Transfer","anonymous":false,"inputs":[
+            {"name":"to","type":"address","indexed":true}]}]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let transfers = contract.events_by_name("Transfer").unwrap();
+        let signature = format!("{:?}", transfers[0].signature());
+        let address = format!("0x{}{}", "00".repeat(12), "aa".repeat(20));
+        let token_id = format!(
+            "0x{}",
+            hex::encode(ethabi::encode(&[Token::Uint(7.into())]))
+        );
+
+        let tx_log = TxLog {
+            topics: vec![signature, address.clone(), address.clone(), token_id],
+            data: "0x".to_string(),
+            ..Default::default()
+        };
+        let decoded = decode_log(&contract, tx_log, false);
+        assert!(decoded.success, "{}", decoded.error_message);
+        assert_eq!(decoded.params[2].name, "tokenId");
+
+        let tx_log = TxLog {
+            topics: vec![format!("{:?}", transfers[2].signature()), address],
+            data: "0x".to_string(),
+            ..Default::default()
+        };
+        let decoded = decode_log(&contract, tx_log, false);
+        assert!(decoded.success, "{}", decoded.error_message);
+        assert_eq!(decoded.params.len(), 1);
+    }
+
+    #[test]
+    fn anonymous_events_are_matched_on_request() {
+        let abi = r#"[{"type":"event","name":"Deposit","anonymous":true,"inputs":[
+            {"name":"amount","type":"uint256","indexed":false}]}]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let tx_log = TxLog {
+            data: format!(
+                "0x{}",
+                hex::encode(ethabi::encode(&[Token::Uint(5.into())]))
+            ),
+            ..Default::default()
+        };
+
+        let decoded = decode_log(&contract, tx_log.clone(), false);
+        assert!(!decoded.success);
+        assert_eq!(decoded.error_code, "abi");
+
+        let decoded = decode_log(&contract, tx_log.clone(), true);
+        assert!(decoded.success, "{}", decoded.error_message);
+        assert_eq!(decoded.event_name, "Deposit");
+        assert_eq!(decoded.params[0].value, "5");
+
+        let decoded = decode_logs_with(Ok(contract), vec![tx_log], true);
+        assert!(decoded[0].success, "{}", decoded[0].error_message);
+    }
+
+    #[test]
+    fn logs_are_kept_when_the_abi_is_unavailable() {
+        let tx_logs = vec![
+            TxLog {
+                log_index: 1,
+                ..Default::default()
+            },
+            TxLog {
+                log_index: 2,
+                ..Default::default()
+            },
+        ];
+
+        let err = Error::Http {
+            status: 404,
+            body: "not found".to_string(),
+        };
+        let decoded = decode_logs_with(Err(err), tx_logs, false);
+
+        assert_eq!(decoded.len(), 2);
+        assert!(decoded.iter().all(|log| log.error_code == "http"));
+        assert_eq!(decoded[1].log.log_index, 2);
+    }
 }
@@ -1,67 +1,149 @@
-use crate::fce_results::{
-    JsonRpcBlockResult, JsonRpcLogResult, JsonRpcResult, JsonRpcTransactionResult,
-};
+use crate::abi_values::parse_type;
+use crate::error::{Error, Result};
+use crate::fce_results::{AbiValuesResult, StringResult, U64Result, U64VecResult};
+use ethabi::{decode, ParamType, Token};
+use ethereum_types::H256;
+use jsonrpc_core as rpc;
 use marine_rs_sdk::marine;
+use serde_json::json;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use ethabi::{decode, ParamType};
 
-pub const BLOCK_NUMBER_TAGS: [&'static str; 3] = ["latest", "earliest", "pending"];
+pub const BLOCK_NUMBER_TAGS: [&str; 5] = ["latest", "earliest", "pending", "safe", "finalized"];
+
+// Largest list util_get_list_blocks_range builds, more would exhaust the module memory
+const MAX_BLOCKS_RANGE: u64 = 100_000;
+
 pub static NONCE_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
 pub fn get_nonce() -> u64 {
     NONCE_COUNTER.fetch_add(1, Ordering::SeqCst) as u64
 }
 
-pub fn check_response_string(response: String, id: &u64) -> JsonRpcResult {
-    if response.len() == 0 {
-        let err_msg = "{\"jsonrpc\":\"$V\",\"id\":$ID,\"error\":{\"code\":-32700,\"message\":Curl connection failed}}";
-        let err_msg = err_msg.replace("$ID", &id.to_string());
-        return JsonRpcResult::from_res(Result::from(Err(err_msg)));
-    }
+/**
+ * Strip the `0x` prefix of a hex string, rejecting input without it
+ */
+pub fn strip_hex_prefix(hex: &str) -> Result<&str> {
+    hex.strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .ok_or_else(|| Error::Hex(format!("{:?} is missing the 0x prefix", hex)))
+}
 
-    match response.contains("error") {
-        true => JsonRpcResult::from_res(Result::from(Err(response))),
-        false => JsonRpcResult::from_res(Result::from(Ok(response))),
-    }
+pub fn parse_hex_u64(hex: &str) -> Result<u64> {
+    Ok(u64::from_str_radix(strip_hex_prefix(hex)?, 16)?)
 }
 
-pub fn check_response_log_string(response: String, id: &u64) -> JsonRpcLogResult {
-    if response.len() == 0 {
-        let err_msg = "{\"jsonrpc\":\"$V\",\"id\":$ID,\"error\":{\"code\":-32700,\"message\":Curl connection failed}}";
-        let err_msg = err_msg.replace("$ID", &id.to_string());
-        return JsonRpcLogResult::from_res(Result::from(Err(err_msg)));
-    }
+/**
+ * A block given to an export: a hex number, a 32 byte hash or one of `BLOCK_NUMBER_TAGS`
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockId {
+    Number(u64),
+    Hash(H256),
+    Tag(&'static str),
+}
+
+impl FromStr for BlockId {
+    type Err = Error;
+
+    fn from_str(block: &str) -> Result<Self> {
+        if let Some(tag) = BLOCK_NUMBER_TAGS.iter().find(|tag| **tag == block) {
+            return Ok(BlockId::Tag(tag));
+        }
 
-    match response.contains("error") {
-        true => JsonRpcLogResult::from_res(Result::from(Err(response))),
-        false => JsonRpcLogResult::from_res(Result::from(Ok(response))),
+        let invalid = || {
+            Error::InvalidArgument(format!(
+                "{:?} is not a block number, hash or one of {}",
+                block,
+                BLOCK_NUMBER_TAGS.join(", ")
+            ))
+        };
+        let digits = strip_hex_prefix(block).map_err(|_| invalid())?;
+
+        if digits.len() == 64 {
+            let hash = hex::decode(digits).map_err(|_| invalid())?;
+            Ok(BlockId::Hash(H256::from_slice(&hash)))
+        } else {
+            let number = u64::from_str_radix(digits, 16).map_err(|_| invalid())?;
+            Ok(BlockId::Number(number))
+        }
     }
 }
 
-pub fn check_response_block_string(response: String, id: &u64) -> JsonRpcBlockResult {
-    if response.len() == 0 {
-        let err_msg = "{\"jsonrpc\":\"$V\",\"id\":$ID,\"error\":{\"code\":-32700,\"message\":Curl connection failed}}";
-        let err_msg = err_msg.replace("$ID", &id.to_string());
-        return JsonRpcBlockResult::from_res(Result::from(Err(err_msg)));
+impl BlockId {
+    /**
+     * Param of calls reading state at a block, hashes use the EIP-1898 object form
+     */
+    pub fn param(&self) -> rpc::Value {
+        match self {
+            BlockId::Number(number) => json!(format!("0x{:x}", number)),
+            BlockId::Hash(hash) => json!({ "blockHash": format!("{:?}", hash) }),
+            BlockId::Tag(tag) => json!(tag),
+        }
+    }
+
+    /**
+     * Param of calls taking a block number or tag only, such as eth_getBlockByNumber
+     */
+    pub fn number_param(&self) -> Result<rpc::Value> {
+        match self {
+            BlockId::Hash(hash) => Err(Error::InvalidArgument(format!(
+                "expected a block number or tag, got hash {:?}",
+                hash
+            ))),
+            _ => Ok(self.param()),
+        }
     }
 
-    match response.contains("error") {
-        true => JsonRpcBlockResult::from_res(Result::from(Err(response))),
-        false => JsonRpcBlockResult::from_res(Result::from(Ok(response))),
+    /**
+     * Param of calls taking a block hash only, such as eth_getBlockByHash
+     */
+    pub fn hash_param(&self) -> Result<rpc::Value> {
+        match self {
+            BlockId::Hash(hash) => Ok(json!(format!("{:?}", hash))),
+            _ => Err(Error::InvalidArgument(format!(
+                "expected a block hash, got {:?}",
+                self
+            ))),
+        }
     }
 }
 
-pub fn check_response_transaction_string(response: String, id: &u64) -> JsonRpcTransactionResult {
-    if response.len() == 0 {
-        let err_msg = "{\"jsonrpc\":\"$V\",\"id\":$ID,\"error\":{\"code\":-32700,\"message\":Curl connection failed}}";
-        let err_msg = err_msg.replace("$ID", &id.to_string());
-        return JsonRpcTransactionResult::from_res(Result::from(Err(err_msg)));
+/**
+ * Check `hex` holds whole bytes and return it lowercased, e.g. contract bytecode
+ */
+pub fn normalize_hex_bytes(hex: &str) -> Result<String> {
+    let digits = strip_hex_prefix(hex)?;
+    hex::decode(digits)?;
+    Ok(format!("0x{}", digits.to_ascii_lowercase()))
+}
+
+/**
+ * Left pad a hex value to a 32 byte word, e.g. a storage slot some nodes return trimmed
+ */
+pub fn normalize_hex_word(hex: &str) -> Result<String> {
+    let digits = strip_hex_prefix(hex)?;
+    if digits.len() > 64 {
+        return Err(Error::Hex(format!("{:?} is longer than 32 bytes", hex)));
     }
+    let word = format!("{:0>64}", digits);
+    hex::decode(&word)?;
+    Ok(format!("0x{}", word.to_ascii_lowercase()))
+}
 
-    match response.contains("error") {
-        true => JsonRpcTransactionResult::from_res(Result::from(Err(response))),
-        false => JsonRpcTransactionResult::from_res(Result::from(Ok(response))),
+/**
+ * Reward percentiles of eth_feeHistory must be within 0..=100 and increasing
+ */
+pub fn validate_reward_percentiles(percentiles: &[f64]) -> Result<()> {
+    let in_range = percentiles.iter().all(|p| (0.0..=100.0).contains(p));
+    let increasing = percentiles.windows(2).all(|w| w[0] <= w[1]);
+    if !in_range || !increasing {
+        return Err(Error::InvalidArgument(format!(
+            "reward percentiles {:?} must be increasing values between 0 and 100",
+            percentiles
+        )));
     }
+    Ok(())
 }
 
 pub fn wei_to_eth(amount: &u128) -> f64 {
@@ -69,8 +151,8 @@ pub fn wei_to_eth(amount: &u128) -> f64 {
 }
 
 #[marine]
-pub fn hex_to_decimal(hex: String) -> u64 {
-    u64::from_str_radix(&hex[2..], 16).unwrap()
+pub fn hex_to_decimal(hex: String) -> U64Result {
+    parse_hex_u64(&hex).into()
 }
 
 #[marine]
@@ -79,62 +161,152 @@ pub fn decimal_to_hex(decimal: u64) -> String {
 }
 
 #[marine]
-pub fn hex_to_string(hex: String) -> String {
-    let bytes = hex::decode(&hex[2..]).unwrap();
-    let mut text = String::from_utf8(bytes).unwrap();
-    text = text.replace(" ", "");
-    text = text.replace("\\", "");
+pub fn hex_to_string(hex: String) -> StringResult {
+    decode_hex_string(&hex).into()
+}
+
+fn decode_hex_string(hex: &str) -> Result<String> {
+    let bytes = hex::decode(strip_hex_prefix(hex)?)?;
+    let mut text = String::from_utf8(bytes)?;
+    text = text.replace(' ', "");
+    text = text.replace('\\', "");
     text = text.trim_end_matches(char::from(0)).to_string();
     text = text.trim_matches(char::from(0)).to_string();
-    text
+    Ok(text)
 }
 
 #[marine]
-pub fn util_get_method_hash(input: String) -> String {
-    let input_str = input.as_str();
-    let input = input_str.strip_prefix("0x").unwrap_or(input_str);
-    let input_bytes = hex::decode(input).unwrap();
-    let b = &input_bytes[0..4];
-    format!("0x{}", hex::encode(&b))
+pub fn util_get_method_hash(input: String) -> StringResult {
+    get_method_hash(&input).into()
+}
+
+fn get_method_hash(input: &str) -> Result<String> {
+    let input = input.strip_prefix("0x").unwrap_or(input);
+    let input_bytes = hex::decode(input)?;
+    let b = input_bytes
+        .get(0..4)
+        .ok_or_else(|| Error::Decoding("input is shorter than a 4 byte selector".to_string()))?;
+    Ok(format!("0x{}", hex::encode(b)))
 }
 
+/**
+ * Block numbers from `start` up to, but not including, `end`
+ */
 #[marine]
-pub fn util_get_list_blocks_range(start: u64, end: u64) -> Vec<u64> {
-    let mut blocks = Vec::new();
+pub fn util_get_list_blocks_range(start: u64, end: u64) -> U64VecResult {
+    blocks_range(start, end).into()
+}
 
-    for n in start..end {
-        blocks.push(n);
+fn blocks_range(start: u64, end: u64) -> Result<Vec<u64>> {
+    if end.saturating_sub(start) > MAX_BLOCKS_RANGE {
+        return Err(Error::InvalidArgument(format!(
+            "range {}..{} has more than {} blocks",
+            start, end, MAX_BLOCKS_RANGE
+        )));
     }
-
-    blocks
+    Ok((start..end).collect())
 }
 
+/**
+ * Decode ABI encoded `data` (with or without 0x) as values of the Solidity types in `abi`
+ */
 #[marine]
-pub fn decode_abi(abi: Vec<String>, data: String) -> Vec<String> {
-  let data_bytes = &hex::decode(&data).unwrap();
+pub fn decode_abi(abi: Vec<String>, data: String) -> AbiValuesResult {
+    decode_abi_values(abi, &data).into()
+}
 
-  let mut new_abi: Vec<ParamType> = Vec::new();
+fn decode_abi_values(abi: Vec<String>, data: &str) -> Result<Vec<(ParamType, Token)>> {
+    let data_bytes = hex::decode(data.strip_prefix("0x").unwrap_or(data))?;
 
-  for row in abi  {
-    if row == "string" {
-      new_abi.push(ParamType::String);
-    }
-    if row == "address" {
-      new_abi.push(ParamType::Address);
-    }
-    if row == "bytes" {
-      new_abi.push(ParamType::Bytes);
+    let types = abi
+        .iter()
+        .map(|type_name| parse_type(type_name))
+        .collect::<Result<Vec<ParamType>>>()?;
+
+    let tokens = decode(&types, &data_bytes).map_err(|err| Error::Decoding(err.to_string()))?;
+
+    Ok(types.into_iter().zip(tokens).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abi_data_is_decoded_with_or_without_prefix() {
+        let data = hex::encode(ethabi::encode(&[
+            Token::Uint(7.into()),
+            Token::Array(vec![Token::Int(ethereum_types::U256::MAX)]),
+        ]));
+        let abi = vec!["uint8".to_string(), "int256[]".to_string()];
+
+        let result = decode_abi(abi.clone(), format!("0x{}", data));
+        assert!(result.success, "{}", result.error_message);
+        assert_eq!(result.values[0].kind, "uint8");
+        assert_eq!(result.values[1].value, r#"["-1"]"#);
+        assert_eq!(result.data, r#"["7",["-1"]]"#);
+
+        assert!(decode_abi(abi, data.clone()).success);
+        assert_eq!(
+            decode_abi(vec!["uint7".to_string()], data).error_code,
+            "invalid_argument"
+        );
     }
-    if row == "int256" || row == "int" {
-      new_abi.push(ParamType::Int(256));
+
+    #[test]
+    fn blocks_range_is_capped() {
+        assert_eq!(util_get_list_blocks_range(5, 8).values, [5, 6, 7]);
+        assert!(util_get_list_blocks_range(8, 5).values.is_empty());
+
+        let result = util_get_list_blocks_range(0, u64::MAX);
+        assert!(!result.success);
+        assert_eq!(result.error_code, "invalid_argument");
     }
-    if row == "bool" {
-      new_abi.push(ParamType::Bool);
+
+    #[test]
+    fn storage_words_are_padded() {
+        assert_eq!(
+            normalize_hex_word("0x1").unwrap(),
+            format!("0x{:0>64}", "1")
+        );
+        assert_eq!(
+            normalize_hex_word("0x").unwrap(),
+            format!("0x{}", "0".repeat(64))
+        );
+        assert!(matches!(
+            normalize_hex_word(&format!("0x{}", "1".repeat(65))),
+            Err(Error::Hex(_))
+        ));
+        assert_eq!(normalize_hex_bytes("0x60AB").unwrap(), "0x60ab");
+        assert!(matches!(normalize_hex_bytes("0x6"), Err(Error::Hex(_))));
     }
-  }
 
-  let results = decode(&new_abi, data_bytes).unwrap();
-  let new_results: Vec<String> = results.into_iter().map(|token| token.clone().to_string()).collect();
+    #[test]
+    fn block_ids_are_validated() {
+        assert_eq!("finalized".parse(), Ok(BlockId::Tag("finalized")));
+        assert_eq!("0x1b4".parse(), Ok(BlockId::Number(436)));
 
-  new_results
+        let hash = format!("0x{}", "ab".repeat(32));
+        let block: BlockId = hash.parse().unwrap();
+        assert_eq!(block.param(), json!({ "blockHash": hash }));
+        assert!(matches!(
+            block.number_param(),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        for invalid in [
+            "",
+            "Latest",
+            "436",
+            "0x",
+            "0xzz",
+            &format!("0x{}", "a".repeat(63)),
+        ] {
+            assert!(
+                matches!(invalid.parse::<BlockId>(), Err(Error::InvalidArgument(_))),
+                "{:?} was accepted",
+                invalid
+            );
+        }
+    }
 }
@@ -1,8 +1,47 @@
+use crate::abi_values::{abi_value, token_json};
+use crate::error::{Error, Result};
+use crate::eth_utils::parse_hex_u64;
 use crate::jsonrpc_helpers::JSON_RPC;
-use crate::types::{ResultSerde, Tx, TxLog, TxSerde, TxSerdeLogs};
+use crate::models::log_param::DataLogParam;
+use crate::types::{
+    Block, BlockSerde, BlockTransactionsSerde, FeeHistory, FeeHistorySerde, Tx, TxLog, TxReceipt,
+    TxReceiptSerde, TxSerde, TxSerdeLogs,
+};
+use ethabi::{ParamType, Token};
 use marine_rs_sdk::marine;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-pub type Result<T> = std::result::Result<T, T>;
+
+// Error object of a failed JSON-RPC call, empty for any other kind of failure
+#[marine]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: String,
+}
+
+impl From<&Error> for JsonRpcError {
+    fn from(err: &Error) -> Self {
+        match err {
+            Error::JsonRpc {
+                code,
+                message,
+                data,
+            }
+            | Error::Reverted {
+                code,
+                message,
+                data,
+            } => Self {
+                code: *code,
+                message: message.clone(),
+                data: data.clone(),
+            },
+            _ => Self::default(),
+        }
+    }
+}
 
 // Result
 #[marine]
@@ -10,37 +49,258 @@ pub type Result<T> = std::result::Result<T, T>;
 pub struct JsonRpcResult {
     pub jsonrpc: String,
     pub result: String,
-    pub error: String,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
     pub id: u64,
 }
 
 impl JsonRpcResult {
-    pub fn from_res(raw_result: Result<String>) -> Self {
+    pub fn from_result(result: Result<String>, id: u64, attempts: u32) -> Self {
         let jsonrpc = JSON_RPC.into();
-        match raw_result {
-            Ok(res) => {
-                let result_obj: Value = serde_json::from_str(&res).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
-                let result = serde_json::from_value(result_obj["result"].clone()).unwrap();
+        match result {
+            Ok(result) => Self {
+                jsonrpc,
+                id,
+                result,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                result: "".to_string(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
+        }
+    }
+}
 
-                Self {
-                    jsonrpc,
-                    id,
-                    result,
-                    error: "".to_string(),
-                }
-            }
-            Err(err) => {
-                let result_obj: Value = serde_json::from_str(&err).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
+// Result of eth_call, `result` is the return data and `revert_data` the data
+// of a reverted call, both hex
+#[marine]
+#[derive(Debug)]
+pub struct JsonRpcCallResult {
+    pub jsonrpc: String,
+    pub result: String,
+    pub reverted: bool,
+    pub revert_data: String,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+    pub id: u64,
+}
 
-                Self {
-                    jsonrpc,
-                    id,
-                    result: "".to_string(),
-                    error: err,
-                }
-            }
+impl JsonRpcCallResult {
+    pub fn from_result(result: Result<String>, id: u64, attempts: u32) -> Self {
+        let jsonrpc = JSON_RPC.into();
+        match result.map_err(Error::into_revert) {
+            Ok(result) => Self {
+                jsonrpc,
+                id,
+                result,
+                reverted: false,
+                revert_data: "".to_string(),
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                result: "".to_string(),
+                reverted: matches!(err, Error::Reverted { .. }),
+                revert_data: match &err {
+                    Error::Reverted { data, .. } => data.clone(),
+                    _ => "".to_string(),
+                },
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
+        }
+    }
+}
+
+// Result holding a quantity, parsed from the node's hex
+#[marine]
+#[derive(Debug)]
+pub struct JsonRpcU64Result {
+    pub jsonrpc: String,
+    pub value: u64,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+    pub id: u64,
+}
+
+impl JsonRpcU64Result {
+    pub fn from_result(result: Result<String>, id: u64, attempts: u32) -> Self {
+        let jsonrpc = JSON_RPC.into();
+        match result.and_then(|result| parse_hex_u64(&result)) {
+            Ok(value) => Self {
+                jsonrpc,
+                id,
+                value,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                value: 0,
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
+        }
+    }
+}
+
+// Fee history
+#[marine]
+#[derive(Debug)]
+pub struct JsonRpcFeeHistoryResult {
+    pub jsonrpc: String,
+    pub fee_history: FeeHistory,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+    pub id: u64,
+}
+
+impl JsonRpcFeeHistoryResult {
+    pub fn from_result(result: Result<FeeHistorySerde>, id: u64, attempts: u32) -> Self {
+        let jsonrpc = JSON_RPC.into();
+        match result.and_then(FeeHistory::try_from) {
+            Ok(fee_history) => Self {
+                jsonrpc,
+                id,
+                fee_history,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                fee_history: FeeHistory::default(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
+        }
+    }
+}
+
+// Result holding a boolean
+#[marine]
+#[derive(Debug)]
+pub struct JsonRpcBoolResult {
+    pub jsonrpc: String,
+    pub value: bool,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+    pub id: u64,
+}
+
+impl JsonRpcBoolResult {
+    pub fn from_result(result: Result<bool>, id: u64, attempts: u32) -> Self {
+        let jsonrpc = JSON_RPC.into();
+        match result {
+            Ok(value) => Self {
+                jsonrpc,
+                id,
+                value,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                value: false,
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
+        }
+    }
+}
+
+// Result holding a list of strings, e.g. block hashes
+#[marine]
+#[derive(Debug)]
+pub struct JsonRpcStringVecResult {
+    pub jsonrpc: String,
+    pub values: Vec<String>,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+    pub id: u64,
+}
+
+impl JsonRpcStringVecResult {
+    pub fn from_result(result: Result<Vec<String>>, id: u64, attempts: u32) -> Self {
+        let jsonrpc = JSON_RPC.into();
+        match result {
+            Ok(values) => Self {
+                jsonrpc,
+                id,
+                values,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                values: Vec::new(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
         }
     }
 }
@@ -51,150 +311,460 @@ impl JsonRpcResult {
 pub struct JsonRpcLogResult {
     pub jsonrpc: String,
     pub result: Vec<TxLog>,
-    pub error: String,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
     pub id: u64,
 }
 
 impl JsonRpcLogResult {
-    pub fn from_res(raw_result: Result<String>) -> Self {
+    pub fn from_result(result: Result<Vec<TxSerdeLogs>>, id: u64, attempts: u32) -> Self {
         let jsonrpc = JSON_RPC.into();
-        match raw_result {
-            Ok(res) => {
-                let result_obj: Value = serde_json::from_str(&res).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
-                let result: Vec<TxSerdeLogs> =
-                    serde_json::from_value(result_obj["result"].clone()).unwrap();
-
-                let tx_logs = result.into_iter().map(|serde| TxLog::from(serde)).collect();
+        let tx_logs = result.and_then(|result| result.into_iter().map(TxLog::try_from).collect());
 
-                Self {
-                    jsonrpc,
-                    id,
-                    result: tx_logs,
-                    error: "".to_string(),
-                }
-            }
-            Err(err) => {
-                let result_obj: Value = serde_json::from_str(&err).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
-
-                Self {
-                    jsonrpc,
-                    id,
-                    result: Vec::new(),
-                    error: err,
-                }
-            }
+        match tx_logs {
+            Ok(tx_logs) => Self {
+                jsonrpc,
+                id,
+                result: tx_logs,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                result: Vec::new(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
         }
     }
 }
-// Block
+// Block, `transactions` is only filled when the block was requested hydrated,
+// `found` is false when the node doesn't know the block
 #[marine]
 #[derive(Debug)]
 pub struct JsonRpcBlockResult {
     pub jsonrpc: String,
+    pub block: Block,
     pub transactions: Vec<Tx>,
-    pub error: String,
+    pub transaction_hashes: Vec<String>,
+    pub found: bool,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
     pub id: u64,
 }
 
 impl JsonRpcBlockResult {
-    pub fn from_res(raw_result: Result<String>) -> Self {
+    pub fn from_result(result: Result<Option<BlockSerde>>, id: u64, attempts: u32) -> Self {
         let jsonrpc = JSON_RPC.into();
-        match raw_result {
-            Ok(res) => {
-                let result_obj: Value = serde_json::from_str(&res).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
-                let result: ResultSerde =
-                    serde_json::from_value(result_obj["result"].clone()).unwrap();
-
-                let txs = result
-                    .transactions
-                    .iter()
-                    .map(|serde| Tx::from(serde))
-                    .collect();
-                Self {
-                    jsonrpc,
-                    id,
-                    transactions: txs,
-                    error: "".to_string(),
-                }
-            }
-            Err(err) => {
-                let result_obj: Value = serde_json::from_str(&err).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
+        let block = result.and_then(|result| {
+            result
+                .map(|block| {
+                    let (txs, hashes) = match &block.transactions {
+                        BlockTransactionsSerde::Hashes(hashes) => (Vec::new(), hashes.clone()),
+                        BlockTransactionsSerde::Full(txs) => {
+                            let txs: Vec<Tx> =
+                                txs.iter().map(Tx::try_from).collect::<Result<_>>()?;
+                            let hashes = txs.iter().map(|tx| tx.hash.clone()).collect();
+                            (txs, hashes)
+                        }
+                    };
+                    Ok((Block::from(&block), txs, hashes))
+                })
+                .transpose()
+        });
 
+        match block {
+            Ok(block) => {
+                let found = block.is_some();
+                let (block, transactions, transaction_hashes) = block.unwrap_or_default();
                 Self {
                     jsonrpc,
                     id,
-                    transactions: Vec::new(),
-                    error: err,
+                    block,
+                    transactions,
+                    transaction_hashes,
+                    found,
+                    success: true,
+                    error_code: "".to_string(),
+                    error_message: "".to_string(),
+                    rpc_error: JsonRpcError::default(),
+                    attempts,
                 }
             }
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                block: Block::default(),
+                transactions: Vec::new(),
+                transaction_hashes: Vec::new(),
+                found: false,
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
         }
     }
 }
 
-// Transaction
+// Transaction, `found` is false when the node doesn't know the transaction
 #[marine]
 #[derive(Debug)]
 pub struct JsonRpcTransactionResult {
     pub jsonrpc: String,
     pub transaction: Tx,
-    pub error: String,
+    pub found: bool,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
     pub id: u64,
 }
 
 impl JsonRpcTransactionResult {
-    pub fn from_res(raw_result: Result<String>) -> Self {
+    pub fn from_result(result: Result<Option<TxSerde>>, id: u64, attempts: u32) -> Self {
         let jsonrpc = JSON_RPC.into();
-        match raw_result {
-            Ok(res) => {
-                let result_obj: Value = serde_json::from_str(&res).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
-                let result: TxSerde = serde_json::from_value(result_obj["result"].clone()).unwrap();
+        let tx = result.and_then(|result| result.as_ref().map(Tx::try_from).transpose());
 
-                Self {
-                    jsonrpc,
-                    id,
-                    transaction: Tx::from(&result),
-                    error: "".to_string(),
-                }
-            }
-            Err(err) => {
-                let result_obj: Value = serde_json::from_str(&err).unwrap();
-                let id: u64 = serde_json::from_value(result_obj["id"].clone()).unwrap();
+        match tx {
+            Ok(tx) => Self {
+                jsonrpc,
+                id,
+                found: tx.is_some(),
+                transaction: tx.unwrap_or_default(),
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                transaction: Tx::default(),
+                found: false,
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
+        }
+    }
+}
 
-                Self {
-                    jsonrpc,
-                    id,
-                    transaction: Tx::default(),
-                    error: err,
-                }
-            }
+// Receipt, `found` is false while the transaction is pending or unknown
+#[marine]
+#[derive(Debug)]
+pub struct JsonRpcReceiptResult {
+    pub jsonrpc: String,
+    pub receipt: TxReceipt,
+    pub found: bool,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+    pub id: u64,
+}
+
+impl JsonRpcReceiptResult {
+    pub fn from_result(result: Result<Option<TxReceiptSerde>>, id: u64, attempts: u32) -> Self {
+        let jsonrpc = JSON_RPC.into();
+        let receipt = result.and_then(|result| result.map(TxReceipt::try_from).transpose());
+
+        match receipt {
+            Ok(receipt) => Self {
+                jsonrpc,
+                id,
+                found: receipt.is_some(),
+                receipt: receipt.unwrap_or_default(),
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts,
+            },
+            Err(err) => Self {
+                jsonrpc,
+                id,
+                receipt: TxReceipt::default(),
+                found: false,
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts,
+            },
         }
     }
 }
 
-// Test
+// String
 #[marine]
 #[derive(Debug)]
-pub struct TestResult {
-    pub test_passed: bool,
-    pub error: String,
+pub struct StringResult {
+    pub value: String,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
 }
 
-impl From<Result<String>> for TestResult {
+impl From<Result<String>> for StringResult {
     fn from(result: Result<String>) -> Self {
         match result {
-            Ok(res) => Self {
-                test_passed: true,
-                error: res,
+            Ok(value) => Self {
+                value,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
             },
             Err(err) => Self {
-                test_passed: false,
-                error: err,
+                value: "".to_string(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
             },
         }
     }
 }
+
+// Values decoded from ABI encoded data, `data` holds them as a JSON array
+#[marine]
+#[derive(Debug)]
+pub struct AbiValuesResult {
+    pub values: Vec<DataLogParam>,
+    pub data: String,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+}
+
+impl From<Result<Vec<(ParamType, Token)>>> for AbiValuesResult {
+    fn from(result: Result<Vec<(ParamType, Token)>>) -> Self {
+        match result {
+            Ok(values) => Self {
+                data: Value::Array(values.iter().map(|(_, token)| token_json(token)).collect())
+                    .to_string(),
+                values: values
+                    .iter()
+                    .map(|(kind, token)| abi_value("", kind, token))
+                    .collect(),
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+            },
+            Err(err) => Self {
+                values: Vec::new(),
+                data: Value::Null.to_string(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+            },
+        }
+    }
+}
+
+// u64
+#[marine]
+#[derive(Debug)]
+pub struct U64Result {
+    pub value: u64,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+}
+
+impl From<Result<u64>> for U64Result {
+    fn from(result: Result<u64>) -> Self {
+        match result {
+            Ok(value) => Self {
+                value,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+            },
+            Err(err) => Self {
+                value: 0,
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+            },
+        }
+    }
+}
+
+// Array<u64>
+#[marine]
+#[derive(Debug)]
+pub struct U64VecResult {
+    pub values: Vec<u64>,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+}
+
+impl From<Result<Vec<u64>>> for U64VecResult {
+    fn from(result: Result<Vec<u64>>) -> Self {
+        match result {
+            Ok(values) => Self {
+                values,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+            },
+            Err(err) => Self {
+                values: Vec::new(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+            },
+        }
+    }
+}
+
+// Outcome of calls that return nothing
+#[marine]
+#[derive(Debug)]
+pub struct EmptyResult {
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+}
+
+impl From<Result<()>> for EmptyResult {
+    fn from(result: Result<()>) -> Self {
+        match result {
+            Ok(()) => Self {
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+            },
+            Err(err) => Self {
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc_helpers::{decode_response, Id};
+
+    #[test]
+    fn receipt_keeps_status_and_log_positions() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":{
+            "transactionHash":"0xaa","transactionIndex":"0x1","blockHash":"0xbb","blockNumber":"0x10",
+            "from":"0x01","to":null,"cumulativeGasUsed":"0x5208","gasUsed":"0x5208",
+            "effectiveGasPrice":"0x64","contractAddress":"0x02","logsBloom":"0x00","type":"0x2","status":"0x0",
+            "logs":[{"address":"0x02","topics":["0xcc"],"data":"0x","blockNumber":"0x10","blockHash":"0xbb",
+                "transactionHash":"0xaa","transactionIndex":"0x1","logIndex":"0x3","removed":false}]
+        }}"#;
+        let result = decode_response(response, &Id::Num(1));
+        let receipt = JsonRpcReceiptResult::from_result(result, 1, 1);
+
+        assert!(receipt.success && receipt.found);
+        assert_eq!(receipt.receipt.status, "0x0");
+        assert_eq!(receipt.receipt.contract_address, "0x02");
+        assert_eq!(receipt.receipt.to, "");
+        assert_eq!(receipt.receipt.logs[0].log_index, 3);
+        assert_eq!(receipt.receipt.logs[0].address, "0x02");
+    }
+
+    #[test]
+    fn block_without_hydration_keeps_hashes_only() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":{
+            "number":"0x10","hash":"0xbb","parentHash":"0xba","timestamp":"0x6400",
+            "baseFeePerGas":"0x64","transactions":["0xaa","0xab"]
+        }}"#;
+        let result = decode_response(response, &Id::Num(1));
+        let block = JsonRpcBlockResult::from_result(result, 1, 1);
+
+        assert!(block.success && block.found);
+        assert_eq!(block.block.parent_hash, "0xba");
+        assert_eq!(block.block.timestamp, "0x6400");
+        assert_eq!(block.transaction_hashes, ["0xaa", "0xab"]);
+        assert!(block.transactions.is_empty());
+    }
+
+    #[test]
+    fn hydrated_block_lists_hashes_too() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":{
+            "number":"0x10","hash":"0xbb","transactions":[{"hash":"0xaa","blockNumber":"0x10"}]
+        }}"#;
+        let result = decode_response(response, &Id::Num(1));
+        let block = JsonRpcBlockResult::from_result(result, 1, 1);
+
+        assert_eq!(block.transactions[0].block_number, "0x10");
+        assert_eq!(block.transaction_hashes, ["0xaa"]);
+    }
+
+    #[test]
+    fn dynamic_fee_transaction_is_complete() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":{
+            "hash":"0xaa","blockHash":null,"blockNumber":null,"transactionIndex":null,
+            "type":"0x2","chainId":"0x13a","maxFeePerGas":"0x64","maxPriorityFeePerGas":"0x1",
+            "accessList":[{"address":"0x02","storageKeys":["0x00"]}],
+            "v":"0x1","r":"0x0a","s":"0x0b"
+        }}"#;
+        let result = decode_response(response, &Id::Num(1));
+        let tx = JsonRpcTransactionResult::from_result(result, 1, 1);
+
+        assert!(tx.success && tx.found);
+        assert!(tx.transaction.pending);
+        assert_eq!(tx.transaction.tx_type, "0x2");
+        assert_eq!(tx.transaction.chain_id, "0x13a");
+        assert_eq!(tx.transaction.max_priority_fee_per_gas, "0x1");
+        assert_eq!(tx.transaction.access_list[0].storage_keys, ["0x00"]);
+        assert_eq!(tx.transaction.s, "0x0b");
+    }
+
+    #[test]
+    fn reverted_call_keeps_revert_data() {
+        let response = r#"{"jsonrpc":"2.0","id":1,"error":{
+            "code":3,"message":"execution reverted: not owner",
+            "data":"0x08c379a00000000000000000000000000000000000000000000000000000000000000020"
+        }}"#;
+        let call = JsonRpcCallResult::from_result(decode_response(response, &Id::Num(1)), 1, 1);
+
+        assert!(!call.success && call.reverted);
+        assert_eq!(call.error_code, "reverted");
+        assert!(call.revert_data.starts_with("0x08c379a0"));
+        assert_eq!(call.rpc_error.code, 3);
+        assert_eq!(call.result, "");
+
+        let response =
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"header not found"}}"#;
+        let call = JsonRpcCallResult::from_result(decode_response(response, &Id::Num(1)), 1, 1);
+
+        assert!(!call.reverted);
+        assert_eq!(call.error_code, "json_rpc");
+    }
+
+    #[test]
+    fn pending_receipt_is_not_an_error() {
+        let result = decode_response(r#"{"jsonrpc":"2.0","id":1,"result":null}"#, &Id::Num(1));
+        let receipt = JsonRpcReceiptResult::from_result(result, 1, 1);
+
+        assert!(receipt.success);
+        assert!(!receipt.found);
+    }
+}
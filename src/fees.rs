@@ -0,0 +1,187 @@
+use crate::error::{Error, Result};
+use crate::eth_calls::serialize;
+use crate::eth_utils::{get_nonce, parse_hex_u64, validate_reward_percentiles};
+use crate::fce_results::JsonRpcError;
+use crate::jsonrpc_helpers::Request;
+use crate::transport::{send_request, Delivery};
+use crate::types::{FeeHistory, FeeHistorySerde};
+use marine_rs_sdk::marine;
+use serde_json::json;
+
+// Number of recent blocks priority fees are sampled from
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+// The base fee rises at most 12.5% per block, twice the current one stays
+// above it for several full blocks in a row
+const BASE_FEE_MULTIPLIER: u64 = 2;
+
+// EIP-1559 fees to sign a transaction with
+#[marine]
+#[derive(Debug, Default)]
+pub struct FeeSuggestionResult {
+    pub base_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+    pub max_fee_per_gas: u64,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct FeeSuggestion {
+    base_fee_per_gas: u64,
+    max_priority_fee_per_gas: u64,
+    max_fee_per_gas: u64,
+}
+
+impl From<Delivery<FeeSuggestion>> for FeeSuggestionResult {
+    fn from(delivery: Delivery<FeeSuggestion>) -> Self {
+        match delivery.result {
+            Ok(fees) => Self {
+                base_fee_per_gas: fees.base_fee_per_gas,
+                max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+                max_fee_per_gas: fees.max_fee_per_gas,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts: delivery.attempts,
+            },
+            Err(err) => Self {
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts: delivery.attempts,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/**
+ * Median of the priority fees paid in the blocks of `history`, blocks
+ * without transactions report 0 and are left out
+ */
+fn median_reward(history: &FeeHistory) -> Option<u64> {
+    let mut rewards: Vec<u64> = history
+        .reward
+        .iter()
+        .filter_map(|rewards| rewards.first().copied())
+        .filter(|reward| *reward > 0)
+        .collect();
+    rewards.sort_unstable();
+
+    rewards.get(rewards.len() / 2).copied()
+}
+
+fn suggest(history: &FeeHistory, max_priority_fee_per_gas: u64) -> Result<FeeSuggestion> {
+    // The last base fee is the one of the next block
+    let base_fee_per_gas = *history
+        .base_fee_per_gas
+        .last()
+        .ok_or_else(|| Error::Decoding("fee history has no base fee".to_string()))?;
+
+    Ok(FeeSuggestion {
+        base_fee_per_gas,
+        max_priority_fee_per_gas,
+        max_fee_per_gas: base_fee_per_gas
+            .saturating_mul(BASE_FEE_MULTIPLIER)
+            .saturating_add(max_priority_fee_per_gas),
+    })
+}
+
+/**
+ * Suggest maxFeePerGas and maxPriorityFeePerGas from the fees paid over the last
+ * blocks: the median of the priority fees at `reward_percentile`, falling back to
+ * eth_maxPriorityFeePerGas when those blocks were empty
+ */
+#[marine]
+pub fn suggest_fees(url: String, reward_percentile: f64) -> FeeSuggestionResult {
+    if let Err(err) = validate_reward_percentiles(&[reward_percentile]) {
+        return Delivery {
+            result: Err(err),
+            attempts: 0,
+        }
+        .into();
+    }
+
+    let params = json!(vec![
+        serialize(&format!("0x{:x}", FEE_HISTORY_BLOCKS)),
+        serialize(&"latest"),
+        serialize(&vec![reward_percentile]),
+    ]);
+    let request = Request::new("eth_feeHistory".to_string(), params, get_nonce());
+    let response = send_request::<FeeHistorySerde>(&url, &request);
+    let mut attempts = response.attempts;
+
+    let history = match response.result.and_then(FeeHistory::try_from) {
+        Ok(history) => history,
+        Err(err) => {
+            return Delivery {
+                result: Err(err),
+                attempts,
+            }
+            .into()
+        }
+    };
+
+    let priority_fee = match median_reward(&history) {
+        Some(reward) => Ok(reward),
+        None => {
+            let request = Request::new(
+                "eth_maxPriorityFeePerGas".to_string(),
+                json!([]),
+                get_nonce(),
+            );
+            let response = send_request::<String>(&url, &request);
+            attempts += response.attempts;
+            response.result.and_then(|fee| parse_hex_u64(&fee))
+        }
+    };
+
+    Delivery {
+        result: priority_fee.and_then(|priority_fee| suggest(&history, priority_fee)),
+        attempts,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestion_uses_next_base_fee_and_median_reward() {
+        let history = FeeHistory {
+            oldest_block: 100,
+            base_fee_per_gas: vec![90, 95, 100, 110],
+            gas_used_ratio: vec![0.5, 0.9, 0.0],
+            reward: vec![vec![3], vec![7], vec![0], vec![5]],
+        };
+
+        let priority_fee = median_reward(&history).unwrap();
+        assert_eq!(priority_fee, 5);
+        assert_eq!(
+            suggest(&history, priority_fee),
+            Ok(FeeSuggestion {
+                base_fee_per_gas: 110,
+                max_priority_fee_per_gas: 5,
+                max_fee_per_gas: 225,
+            })
+        );
+    }
+
+    #[test]
+    fn empty_blocks_give_no_reward() {
+        let history = FeeHistory {
+            reward: vec![vec![0], vec![0]],
+            ..Default::default()
+        };
+
+        assert_eq!(median_reward(&history), None);
+        assert!(matches!(suggest(&history, 1), Err(Error::Decoding(_))));
+    }
+}
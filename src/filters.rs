@@ -0,0 +1,177 @@
+use crate::error::{Error, Result};
+use crate::eth_calls::{log_filter, request_logs, serialize};
+use crate::eth_utils::get_nonce;
+use crate::fce_results::{JsonRpcBoolResult, JsonRpcResult, JsonRpcStringVecResult};
+use crate::jsonrpc_helpers::Request;
+use crate::models::log_param::EventLogsResult;
+use crate::transport::{endpoints, send_request};
+use jsonrpc_core as rpc;
+use marine_rs_sdk::marine;
+use serde_json::json;
+
+/**
+ * Filters only exist on the node that created them, a failover list could
+ * send a poll to a node that never heard of the filter
+ */
+fn filter_endpoint(url: &str) -> Result<&str> {
+    match endpoints(url).len() {
+        1 => Ok(url.trim()),
+        count => Err(Error::InvalidArgument(format!(
+            "filters need a single endpoint, got {}",
+            count
+        ))),
+    }
+}
+
+/**
+ * Install a log filter on the node, returns its id to poll with eth_get_filter_changes
+ */
+#[marine]
+pub fn eth_new_filter(
+    url: String,
+    start_block_in_hex: &str,
+    end_block_in_hex: &str,
+    address: &str,
+    topics: Vec<String>,
+) -> JsonRpcResult {
+    let method = "eth_newFilter".to_string();
+
+    let filter = filter_endpoint(&url).and_then(|endpoint| {
+        let filter = log_filter(start_block_in_hex, end_block_in_hex, address, topics)?;
+        Ok((endpoint, filter))
+    });
+    let (endpoint, filter) = match filter {
+        Ok(filter) => filter,
+        Err(err) => return JsonRpcResult::from_result(Err(err), 0, 0),
+    };
+    let params: rpc::Value = json!(vec![filter]);
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(endpoint, &request);
+
+    JsonRpcResult::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Install a filter notified of new blocks, polled with eth_get_block_filter_changes
+ */
+#[marine]
+pub fn eth_new_block_filter(url: String) -> JsonRpcResult {
+    let method = "eth_newBlockFilter".to_string();
+    let params: rpc::Value = json!([]);
+
+    let endpoint = match filter_endpoint(&url) {
+        Ok(endpoint) => endpoint,
+        Err(err) => return JsonRpcResult::from_result(Err(err), 0, 0),
+    };
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(endpoint, &request);
+
+    JsonRpcResult::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Logs matching a log filter since it was last polled, decoded with the ABI at `abi_url`
+ */
+#[marine]
+pub fn eth_get_filter_changes(
+    url: String,
+    abi_url: String,
+    filter_id: String,
+    match_anonymous: bool,
+) -> EventLogsResult {
+    let method = "eth_getFilterChanges".to_string();
+    let params: rpc::Value = json!(vec![serialize(&filter_id)]);
+
+    match filter_endpoint(&url) {
+        Ok(endpoint) => request_logs(endpoint, abi_url, method, params, match_anonymous),
+        Err(err) => Err(err).into(),
+    }
+}
+
+/**
+ * Hashes of the blocks added since a block filter was last polled
+ */
+#[marine]
+pub fn eth_get_block_filter_changes(url: String, filter_id: String) -> JsonRpcStringVecResult {
+    let method = "eth_getFilterChanges".to_string();
+    let params: rpc::Value = json!(vec![serialize(&filter_id)]);
+
+    let endpoint = match filter_endpoint(&url) {
+        Ok(endpoint) => endpoint,
+        Err(err) => return JsonRpcStringVecResult::from_result(Err(err), 0, 0),
+    };
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(endpoint, &request);
+
+    JsonRpcStringVecResult::from_result(response.result, id, response.attempts)
+}
+
+/**
+ * Every log matching a log filter, decoded with the ABI at `abi_url`
+ */
+#[marine]
+pub fn eth_get_filter_logs(
+    url: String,
+    abi_url: String,
+    filter_id: String,
+    match_anonymous: bool,
+) -> EventLogsResult {
+    let method = "eth_getFilterLogs".to_string();
+    let params: rpc::Value = json!(vec![serialize(&filter_id)]);
+
+    match filter_endpoint(&url) {
+        Ok(endpoint) => request_logs(endpoint, abi_url, method, params, match_anonymous),
+        Err(err) => Err(err).into(),
+    }
+}
+
+/**
+ * Remove a filter, `value` is false when the node did not know it
+ */
+#[marine]
+pub fn eth_uninstall_filter(url: String, filter_id: String) -> JsonRpcBoolResult {
+    let method = "eth_uninstallFilter".to_string();
+    let params: rpc::Value = json!(vec![serialize(&filter_id)]);
+
+    let endpoint = match filter_endpoint(&url) {
+        Ok(endpoint) => endpoint,
+        Err(err) => return JsonRpcBoolResult::from_result(Err(err), 0, 0),
+    };
+
+    let id = get_nonce();
+
+    let request = Request::new(method, params, id);
+    let response = send_request(endpoint, &request);
+
+    JsonRpcBoolResult::from_result(response.result, id, response.attempts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_need_a_single_endpoint() {
+        assert_eq!(
+            filter_endpoint(" https://api.node.glif.io/rpc/v1 "),
+            Ok("https://api.node.glif.io/rpc/v1")
+        );
+        assert!(matches!(
+            filter_endpoint("https://a.example.com,https://b.example.com"),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            filter_endpoint(""),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
@@ -14,16 +14,45 @@
  * limitations under the License.
  */
 
+use crate::error::{Error, Result};
 use jsonrpc_core as rpc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
 
-pub const JSON_RPC: &'static str = "2.0";
+pub const JSON_RPC: &str = "2.0";
 
-#[derive(Debug)]
+// A request id, nodes echo back whichever form they were sent
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Id {
+    Num(u64),
+    Str(String),
+}
+
+impl From<u64> for Id {
+    fn from(id: u64) -> Self {
+        Id::Num(id)
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Id::Num(id) => write!(f, "{}", id),
+            Id::Str(id) => write!(f, "{:?}", id),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
 pub struct Request {
     pub jsonrpc: String,
     pub method: String,
     pub params: rpc::Value,
-    pub id: u64,
+    pub id: Id,
 }
 
 impl Request {
@@ -32,26 +61,223 @@ impl Request {
             jsonrpc: String::from(JSON_RPC),
             method,
             params,
-            id,
+            id: Id::from(id),
+        }
+    }
+
+    pub fn body(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+// Several requests sent to the node as one JSON array
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct BatchRequest {
+    pub requests: Vec<Request>,
+}
+
+impl BatchRequest {
+    pub fn new(requests: Vec<Request>) -> Self {
+        BatchRequest { requests }
+    }
+
+    pub fn ids(&self) -> Vec<Id> {
+        self.requests
+            .iter()
+            .map(|request| request.id.clone())
+            .collect()
+    }
+
+    pub fn body(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+// The `error` member of a JSON-RPC response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorObject {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl From<ErrorObject> for Error {
+    fn from(err: ErrorObject) -> Self {
+        let data = match err.data {
+            Value::Null => "".to_string(),
+            Value::String(data) => data,
+            data => data.to_string(),
+        };
+
+        Error::JsonRpc {
+            code: err.code,
+            message: err.message,
+            data,
         }
     }
+}
+
+// A JSON-RPC response envelope, `id` is null when the node could not read the request id
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Response<T = Value> {
+    pub jsonrpc: Option<String>,
+    pub id: Option<Id>,
+    pub result: Option<T>,
+    pub error: Option<ErrorObject>,
+}
+
+impl<T: DeserializeOwned> Response<T> {
+    pub fn into_result(self, id: &Id) -> Result<T> {
+        // Errors about requests the node could not parse come back with a null id
+        let id_unknown = self.error.is_some() && self.id.is_none();
+        if self.id.as_ref() != Some(id) && !id_unknown {
+            return Err(Error::MalformedJson(format!(
+                "response id {} does not match request id {}",
+                self.id
+                    .map(|id| id.to_string())
+                    .unwrap_or_else(|| "null".to_string()),
+                id
+            )));
+        }
 
-    pub fn as_sys_string(&self, url: &String) -> Vec<String> {
-        let mut v = vec!["-s".to_string()];
-        v.push("-X".to_string());
-        v.push("POST".to_string());
-        v.push("-H".to_string());
-        v.push("Content-Type: application/json".to_string());
-        v.push("-d".to_string());
-        let data = format!(
-            "{{\"jsonrpc\":\"{}\", \"method\":\"{}\", \"params\":{}, \"id\":{}}}",
-            self.jsonrpc,
-            self.method,
-            self.params.to_string(),
-            self.id
+        match (self.error, self.result) {
+            (Some(err), _) => Err(err.into()),
+            (None, Some(result)) => Ok(result),
+            // A null result is only valid for types that can hold it
+            (None, None) => Ok(serde_json::from_value(Value::Null)?),
+        }
+    }
+}
+
+/**
+ * Decode a raw JSON-RPC response into its typed `result` or the error it carries
+ */
+pub fn decode_response<T: DeserializeOwned>(response: &str, id: &Id) -> Result<T> {
+    let response: Response<T> = serde_json::from_str(response)?;
+    response.into_result(id)
+}
+
+/**
+ * Decode a batch response, matching every entry back to its request by id.
+ * Results are returned in the order of `ids` whatever order the node answered in.
+ */
+pub fn decode_batch_response<T: DeserializeOwned>(
+    response: &str,
+    ids: &[Id],
+) -> Result<Vec<Result<T>>> {
+    let responses: Vec<Response> = match serde_json::from_str(response)? {
+        Value::Array(responses) => responses
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<std::result::Result<_, _>>()?,
+        // A node rejecting the whole batch answers with a single error object
+        single => {
+            let single: Response = serde_json::from_value(single)?;
+            return Err(single.error.map(Error::from).unwrap_or_else(|| {
+                Error::MalformedJson("batch response is not an array".to_string())
+            }));
+        }
+    };
+
+    let mut by_id: HashMap<Id, Response> = responses
+        .into_iter()
+        .filter_map(|response| response.id.clone().map(|id| (id, response)))
+        .collect();
+
+    let results = ids
+        .iter()
+        .map(|id| match by_id.remove(id) {
+            Some(response) => response
+                .into_result(id)
+                .and_then(|result| Ok(serde_json::from_value(result)?)),
+            None => Err(Error::MalformedJson(format!(
+                "batch response has no entry for request id {}",
+                id
+            ))),
+        })
+        .collect();
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn request_body_escapes_method() {
+        let request = Request::new("eth_call\", \"id\": 0, \"x\":\"".to_string(), json!([]), 7);
+        let body: Value = serde_json::from_str(&request.body().unwrap()).unwrap();
+
+        assert_eq!(body["method"], "eth_call\", \"id\": 0, \"x\":\"");
+        assert_eq!(body["id"], 7);
+        assert_eq!(body["jsonrpc"], JSON_RPC);
+    }
+
+    #[test]
+    fn response_ids_can_be_strings() {
+        let id = Id::Str("abc".to_string());
+        let result: String =
+            decode_response(r#"{"jsonrpc":"2.0","id":"abc","result":"0x1"}"#, &id).unwrap();
+
+        assert_eq!(result, "0x1");
+    }
+
+    #[test]
+    fn response_with_other_id_is_rejected() {
+        let result =
+            decode_response::<String>(r#"{"jsonrpc":"2.0","id":2,"result":"0x1"}"#, &Id::Num(1));
+
+        assert!(matches!(result, Err(Error::MalformedJson(_))));
+    }
+
+    #[test]
+    fn error_object_is_returned_even_without_id() {
+        let response =
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32700,"message":"parse error"}}"#;
+        let result = decode_response::<String>(response, &Id::Num(1));
+
+        assert_eq!(
+            result,
+            Err(Error::JsonRpc {
+                code: -32700,
+                message: "parse error".to_string(),
+                data: "".to_string(),
+            })
         );
-        v.push(data);
-        v.push(url.to_string());
-        v
+    }
+
+    #[test]
+    fn batch_entries_are_matched_by_id() {
+        let response = r#"[
+            {"jsonrpc":"2.0","id":3,"result":"0x3"},
+            {"jsonrpc":"2.0","id":1,"result":"0x1"},
+            {"jsonrpc":"2.0","id":2,"error":{"code":-32000,"message":"header not found"}}
+        ]"#;
+        let ids = [Id::Num(1), Id::Num(2), Id::Num(3), Id::Num(4)];
+        let results = decode_batch_response::<String>(response, &ids).unwrap();
+
+        assert_eq!(results[0], Ok("0x1".to_string()));
+        assert!(matches!(
+            results[1],
+            Err(Error::JsonRpc { code: -32000, .. })
+        ));
+        assert_eq!(results[2], Ok("0x3".to_string()));
+        assert!(matches!(results[3], Err(Error::MalformedJson(_))));
+    }
+
+    #[test]
+    fn batch_serializes_as_array() {
+        let batch = BatchRequest::new(vec![
+            Request::new("eth_blockNumber".to_string(), json!([]), 1),
+            Request::new("eth_chainId".to_string(), json!([]), 2),
+        ]);
+        let body: Value = serde_json::from_str(&batch.body().unwrap()).unwrap();
+
+        assert_eq!(body.as_array().unwrap().len(), 2);
+        assert_eq!(body[1]["method"], "eth_chainId");
     }
 }
@@ -1,15 +1,23 @@
 use marine_rs_sdk::marine;
 use marine_rs_sdk::module_manifest;
+use marine_rs_sdk::CallParameters;
 use marine_rs_sdk::MountedBinaryResult;
 use marine_rs_sdk::WasmLoggerBuilder;
 
+mod abi_values;
 mod bytes_type;
+pub mod endpoint_config;
+mod error;
 pub mod eth_calls;
 pub mod eth_contract;
 pub mod eth_utils;
 mod fce_results;
+pub mod fees;
+pub mod filters;
 mod jsonrpc_helpers;
 mod models;
+pub mod networks;
+pub mod transport;
 mod types;
 
 module_manifest!();
@@ -21,11 +29,41 @@ pub fn main() {
         .unwrap();
 }
 
-pub fn curl_request_res(curl_cmd: Vec<String>) -> Result<String, std::string::FromUtf8Error> {
-    log::info!("curl cmd: {:?}", curl_cmd);
-    let response = curl(curl_cmd);
-    let res = String::from_utf8(response.stdout)?;
-    Ok(res)
+/**
+ * Run curl with `curl_args` against `url`, the url is validated before curl ever sees it
+ */
+pub fn curl_request_res(
+    curl_args: Vec<String>,
+    url: &str,
+) -> error::Result<transport::CurlResponse> {
+    let config = endpoint_config::endpoint_config(url);
+    let curl_cmd = transport::curl_command(curl_args, url, &config)?;
+    log::info!(
+        "curl cmd: {:?}",
+        endpoint_config::redact_curl_command(&curl_cmd)
+    );
+    let response = transport::CurlResponse::from_mounted(curl(curl_cmd))?;
+    log::info!(
+        "curl exit code: {}, http status: {}",
+        response.exit_code,
+        response.http_status
+    );
+    response.within_limit(config.max_response_bytes)
+}
+
+/**
+ * Service wide settings, shared by every caller, may only be changed by the
+ * peer that created the service
+ */
+pub fn ensure_service_owner(call_parameters: &CallParameters) -> error::Result<()> {
+    let is_owner = !call_parameters.init_peer_id.is_empty()
+        && call_parameters.init_peer_id == call_parameters.service_creator_peer_id;
+    if !is_owner {
+        return Err(error::Error::InvalidConfig(
+            "only the service owner may change service settings".to_string(),
+        ));
+    }
+    Ok(())
 }
 
 #[marine]
@@ -0,0 +1,49 @@
+use crate::error::Error;
+use crate::fce_results::JsonRpcError;
+use crate::models::log_param::DataLogParam;
+use marine_rs_sdk::marine;
+use serde_json::Value;
+
+// Why a call reverted. `kind` is "error" for Error(string), "panic" for
+// Panic(uint256), "custom" for an error of the ABI and "unknown" when the
+// revert data matches none of them.
+#[marine]
+#[derive(Debug, Default)]
+pub struct RevertReason {
+    pub kind: String,
+    pub name: String,
+    pub message: String,
+    pub params: Vec<DataLogParam>,
+    pub data: String,
+}
+
+// Outcome of contract_view_call: `result` is the raw return data, `outputs`
+// the values it decodes to and `data` those values as a JSON object
+#[marine]
+#[derive(Debug, Default)]
+pub struct ContractCallResult {
+    pub result: String,
+    pub outputs: Vec<DataLogParam>,
+    pub data: String,
+    pub reverted: bool,
+    pub revert: RevertReason,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+    pub id: u64,
+}
+
+impl ContractCallResult {
+    pub fn from_err(err: Error) -> Self {
+        Self {
+            data: Value::Null.to_string(),
+            success: false,
+            error_code: err.code().to_string(),
+            error_message: err.to_string(),
+            rpc_error: JsonRpcError::from(&err),
+            ..Default::default()
+        }
+    }
+}
@@ -1,7 +1,10 @@
+use crate::error::{Error, Result};
+use crate::fce_results::{JsonRpcError, JsonRpcLogResult};
+use crate::types::TxLog;
 use ethabi::EventParam;
 use marine_rs_sdk::marine;
 use serde::{Deserialize, Serialize};
-use serde_json::{Value};
+use serde_json::Value;
 
 #[marine]
 #[derive(Debug, Serialize, Deserialize)]
@@ -9,10 +12,76 @@ pub struct EventLogParamResult {
     pub event_name: String,
     pub params: Vec<DataLogParam>,
     pub success: bool,
-    pub error_msg: String,
+    pub error_code: String,
+    pub error_message: String,
     pub data: String,
     pub block_number: u64,
     pub transaction_hash: String,
+    // The log as returned by the node, kept whether it could be decoded or not
+    pub log: TxLog,
+}
+
+impl EventLogParamResult {
+    pub fn from_err(err: Error, log: TxLog) -> Self {
+        Self {
+            event_name: "".to_string(),
+            params: Vec::new(),
+            success: false,
+            error_code: err.code().to_string(),
+            error_message: err.to_string(),
+            data: Value::Null.to_string(),
+            block_number: log.block_number,
+            transaction_hash: log.transaction_hash.clone(),
+            log,
+        }
+    }
+}
+
+#[marine]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventLogsResult {
+    pub logs: Vec<EventLogParamResult>,
+    pub success: bool,
+    pub error_code: String,
+    pub error_message: String,
+    pub rpc_error: JsonRpcError,
+    pub attempts: u32,
+}
+
+impl From<JsonRpcLogResult> for EventLogsResult {
+    fn from(failed: JsonRpcLogResult) -> Self {
+        Self {
+            logs: Vec::new(),
+            success: false,
+            error_code: failed.error_code,
+            error_message: failed.error_message,
+            rpc_error: failed.rpc_error,
+            attempts: failed.attempts,
+        }
+    }
+}
+
+impl From<Result<Vec<EventLogParamResult>>> for EventLogsResult {
+    fn from(result: Result<Vec<EventLogParamResult>>) -> Self {
+        match result {
+            Ok(logs) => Self {
+                logs,
+                success: true,
+                error_code: "".to_string(),
+                error_message: "".to_string(),
+                rpc_error: JsonRpcError::default(),
+                attempts: 0,
+            },
+            Err(err) => Self {
+                logs: Vec::new(),
+                success: false,
+                error_code: err.code().to_string(),
+                error_message: err.to_string(),
+                rpc_error: JsonRpcError::from(&err),
+                attempts: 0,
+            },
+        }
+    }
 }
 
 #[marine]
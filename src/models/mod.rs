@@ -1 +1,2 @@
+pub mod contract_call;
 pub mod log_param;
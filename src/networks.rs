@@ -0,0 +1,401 @@
+use crate::ensure_service_owner;
+use crate::error::{Error, Result};
+use crate::eth_calls::{
+    eth_get_balance, eth_get_block_by_hash, eth_get_block_by_number, eth_get_latest_block_number,
+    eth_get_logs, eth_get_transaction_by_hash, eth_get_transaction_receipt,
+    eth_send_raw_transaction,
+};
+use crate::eth_utils::{get_nonce, parse_hex_u64};
+use crate::fce_results::{
+    EmptyResult, JsonRpcBlockResult, JsonRpcReceiptResult, JsonRpcResult, JsonRpcTransactionResult,
+};
+use crate::jsonrpc_helpers::Request;
+use crate::models::log_param::EventLogsResult;
+use crate::transport::{send_request, validate_url};
+use marine_rs_sdk::{get_call_parameters, marine};
+use serde_json::json;
+use std::sync::Mutex;
+
+// Networks known without registration, (name, chain id, endpoints)
+const KNOWN_NETWORKS: [(&str, u64, &[&str]); 2] = [
+    ("filecoin", 314, &["https://api.node.glif.io/rpc/v1"]),
+    (
+        "calibration",
+        314159,
+        &["https://api.calibration.node.glif.io/rpc/v1"],
+    ),
+];
+
+static NETWORKS: Mutex<Vec<NetworkEntry>> = Mutex::new(Vec::new());
+
+// A chain reachable through an ordered list of endpoints
+#[marine]
+#[derive(Debug, Clone, Default)]
+pub struct Network {
+    pub name: String,
+    pub endpoints: Vec<String>,
+    pub chain_id: u64,
+}
+
+#[derive(Debug, Clone)]
+struct NetworkEntry {
+    network: Network,
+    // Endpoints whose eth_chainId has been checked against `network.chain_id`
+    verified: Vec<String>,
+}
+
+impl Network {
+    fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(Error::InvalidConfig("network name is empty".to_string()));
+        }
+        if self.chain_id == 0 {
+            return Err(Error::InvalidConfig(format!(
+                "network {} has no chain id",
+                self.name
+            )));
+        }
+        if self.endpoints.is_empty() {
+            return Err(Error::InvalidConfig(format!(
+                "network {} has no endpoints",
+                self.name
+            )));
+        }
+        for endpoint in self.endpoints.iter() {
+            validate_url(endpoint)?;
+        }
+        Ok(())
+    }
+}
+
+fn known_network(name: &str) -> Option<Network> {
+    KNOWN_NETWORKS
+        .iter()
+        .find(|(known, _, _)| *known == name)
+        .map(|(name, chain_id, endpoints)| Network {
+            name: name.to_string(),
+            endpoints: endpoints
+                .iter()
+                .map(|endpoint| endpoint.to_string())
+                .collect(),
+            chain_id: *chain_id,
+        })
+}
+
+/**
+ * Register (or replace) a network, its endpoints are verified again on next use.
+ * Only the service owner may do so, every caller's traffic follows the registry.
+ */
+#[marine]
+pub fn register_network(network: Network) -> EmptyResult {
+    ensure_service_owner(&get_call_parameters())
+        .and_then(|_| add_network(network))
+        .into()
+}
+
+fn add_network(network: Network) -> Result<()> {
+    network.validate()?;
+
+    let mut networks = NETWORKS
+        .lock()
+        .map_err(|_| Error::InvalidConfig("network registry is unavailable".to_string()))?;
+    networks.retain(|entry| entry.network.name != network.name);
+    networks.push(NetworkEntry {
+        network,
+        verified: Vec::new(),
+    });
+    Ok(())
+}
+
+/**
+ * Remove a registered network, known networks fall back to their default endpoints
+ */
+#[marine]
+pub fn unregister_network(name: String) -> EmptyResult {
+    ensure_service_owner(&get_call_parameters())
+        .map(|_| remove_network(&name))
+        .into()
+}
+
+fn remove_network(name: &str) {
+    if let Ok(mut networks) = NETWORKS.lock() {
+        networks.retain(|entry| entry.network.name != name);
+    }
+}
+
+/**
+ * Registered networks followed by the known ones that were not overridden
+ */
+#[marine]
+pub fn list_networks() -> Vec<Network> {
+    let mut networks: Vec<Network> = NETWORKS
+        .lock()
+        .map(|networks| networks.iter().map(|entry| entry.network.clone()).collect())
+        .unwrap_or_default();
+
+    for (name, _, _) in KNOWN_NETWORKS.iter() {
+        if !networks.iter().any(|network| network.name == *name) {
+            networks.extend(known_network(name));
+        }
+    }
+
+    networks
+}
+
+fn lookup(name: &str) -> Result<NetworkEntry> {
+    let registered = NETWORKS.lock().ok().and_then(|networks| {
+        networks
+            .iter()
+            .find(|entry| entry.network.name == name)
+            .cloned()
+    });
+
+    registered
+        .or_else(|| {
+            known_network(name).map(|network| NetworkEntry {
+                network,
+                verified: Vec::new(),
+            })
+        })
+        .ok_or_else(|| Error::InvalidConfig(format!("unknown network {:?}", name)))
+}
+
+fn store(entry: NetworkEntry) {
+    if let Ok(mut networks) = NETWORKS.lock() {
+        networks.retain(|registered| registered.network.name != entry.network.name);
+        networks.push(entry);
+    }
+}
+
+fn chain_id(endpoint: &str) -> Result<u64> {
+    let request = Request::new("eth_chainId".to_string(), json!([]), get_nonce());
+    let chain_id: String = send_request(endpoint, &request).result?;
+    parse_hex_u64(&chain_id)
+}
+
+/**
+ * Endpoints of a network joined into a failover list. Each endpoint has its
+ * eth_chainId checked on first use, unreachable ones are checked again later.
+ */
+pub fn resolve_network(name: &str) -> Result<String> {
+    let mut entry = lookup(name)?;
+    let mut last_err = None;
+
+    for endpoint in entry.network.endpoints.iter() {
+        if entry.verified.contains(endpoint) {
+            continue;
+        }
+
+        match chain_id(endpoint) {
+            Ok(actual) if actual == entry.network.chain_id => entry.verified.push(endpoint.clone()),
+            Ok(actual) => {
+                return Err(Error::ChainMismatch {
+                    network: entry.network.name,
+                    expected: entry.network.chain_id,
+                    actual,
+                })
+            }
+            Err(err) => {
+                log::info!("network {}: {}", name, err);
+                last_err = Some(err);
+            }
+        }
+    }
+
+    let endpoints: Vec<String> = entry
+        .network
+        .endpoints
+        .iter()
+        .filter(|endpoint| entry.verified.contains(endpoint))
+        .cloned()
+        .collect();
+    store(entry);
+
+    if endpoints.is_empty() {
+        return Err(last_err.unwrap_or_else(|| {
+            Error::Transport(format!("no endpoint of network {} is reachable", name))
+        }));
+    }
+    Ok(endpoints.join(","))
+}
+
+#[marine]
+pub fn eth_get_latest_block_number_on_network(network: String) -> JsonRpcResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_get_latest_block_number(url),
+        Err(err) => JsonRpcResult::from_result(Err(err), 0, 0),
+    }
+}
+
+#[marine]
+pub fn eth_get_block_by_number_on_network(
+    network: String,
+    block_in_hex: String,
+    hydrated: bool,
+) -> JsonRpcBlockResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_get_block_by_number(url, block_in_hex, hydrated),
+        Err(err) => JsonRpcBlockResult::from_result(Err(err), 0, 0),
+    }
+}
+
+#[marine]
+pub fn eth_get_block_by_hash_on_network(
+    network: String,
+    block_hash: String,
+    hydrated: bool,
+) -> JsonRpcBlockResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_get_block_by_hash(url, block_hash, hydrated),
+        Err(err) => JsonRpcBlockResult::from_result(Err(err), 0, 0),
+    }
+}
+
+#[marine]
+pub fn eth_get_transaction_by_hash_on_network(
+    network: String,
+    trans_hash: String,
+) -> JsonRpcTransactionResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_get_transaction_by_hash(url, trans_hash),
+        Err(err) => JsonRpcTransactionResult::from_result(Err(err), 0, 0),
+    }
+}
+
+#[marine]
+pub fn eth_get_transaction_receipt_on_network(
+    network: String,
+    trans_hash: String,
+) -> JsonRpcReceiptResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_get_transaction_receipt(url, trans_hash),
+        Err(err) => JsonRpcReceiptResult::from_result(Err(err), 0, 0),
+    }
+}
+
+#[marine]
+pub fn eth_send_raw_transaction_on_network(network: String, signed_tx: String) -> JsonRpcResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_send_raw_transaction(url, signed_tx),
+        Err(err) => JsonRpcResult::from_result(Err(err), 0, 0),
+    }
+}
+
+#[marine]
+pub fn eth_get_balance_on_network(network: String, add: String, block: String) -> JsonRpcResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_get_balance(url, add, block),
+        Err(err) => JsonRpcResult::from_result(Err(err), 0, 0),
+    }
+}
+
+#[marine]
+pub fn eth_get_logs_on_network(
+    network: String,
+    abi_url: String,
+    start_block_in_hex: &str,
+    end_block_in_hex: &str,
+    address: &str,
+    topics: Vec<String>,
+    match_anonymous: bool,
+) -> EventLogsResult {
+    match resolve_network(&network) {
+        Ok(url) => eth_get_logs(
+            url,
+            abi_url,
+            start_block_in_hex,
+            end_block_in_hex,
+            address,
+            topics,
+            match_anonymous,
+        ),
+        Err(err) => Err(err).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use marine_rs_sdk::CallParameters;
+
+    #[test]
+    fn only_the_service_owner_changes_settings() {
+        let owner = CallParameters {
+            init_peer_id: "12D3KooWOwner".to_string(),
+            service_creator_peer_id: "12D3KooWOwner".to_string(),
+            ..Default::default()
+        };
+        assert!(ensure_service_owner(&owner).is_ok());
+
+        let other = CallParameters {
+            init_peer_id: "12D3KooWOther".to_string(),
+            ..owner.clone()
+        };
+        assert!(matches!(
+            ensure_service_owner(&other),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(ensure_service_owner(&CallParameters::default()).is_err());
+    }
+
+    #[test]
+    fn known_networks_are_listed() {
+        let networks = list_networks();
+        let calibration = networks
+            .iter()
+            .find(|network| network.name == "calibration")
+            .unwrap();
+
+        assert_eq!(calibration.chain_id, 314159);
+        assert!(!calibration.endpoints.is_empty());
+    }
+
+    #[test]
+    fn invalid_networks_are_rejected() {
+        let network = Network {
+            name: "local".to_string(),
+            endpoints: vec!["file:///etc/passwd".to_string()],
+            chain_id: 31415926,
+        };
+        assert!(matches!(add_network(network), Err(Error::InvalidUrl(_))));
+
+        let network = Network {
+            name: "local".to_string(),
+            endpoints: vec!["http://127.0.0.1:1234/rpc/v1?a=1,b=2".to_string()],
+            chain_id: 31415926,
+        };
+        assert!(matches!(add_network(network), Err(Error::InvalidUrl(_))));
+
+        let network = Network {
+            name: "local".to_string(),
+            endpoints: vec!["http://127.0.0.1:1234/rpc/v1".to_string()],
+            chain_id: 0,
+        };
+        assert!(matches!(add_network(network), Err(Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn registered_network_overrides_known_one() {
+        assert!(matches!(lookup("nope"), Err(Error::InvalidConfig(_))));
+
+        let network = Network {
+            name: "filecoin".to_string(),
+            endpoints: vec!["https://filecoin.example.com/rpc/v1".to_string()],
+            chain_id: 314,
+        };
+        assert!(add_network(network).is_ok());
+
+        let entry = lookup("filecoin").unwrap();
+        assert_eq!(
+            entry.network.endpoints,
+            ["https://filecoin.example.com/rpc/v1"]
+        );
+        assert!(entry.verified.is_empty());
+
+        remove_network("filecoin");
+        assert_eq!(
+            lookup("filecoin").unwrap().network.endpoints,
+            ["https://api.node.glif.io/rpc/v1"]
+        );
+    }
+}
@@ -0,0 +1,838 @@
+use crate::endpoint_config::{redact_url, EndpointConfig};
+use crate::error::{Error, Result};
+use crate::fce_results::EmptyResult;
+use crate::jsonrpc_helpers::{
+    decode_batch_response, decode_response, BatchRequest, Id, Request, Response,
+};
+use crate::{curl_request_res, ensure_service_owner};
+use marine_rs_sdk::MountedBinaryResult;
+use marine_rs_sdk::{get_call_parameters, marine};
+use serde::de::DeserializeOwned;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+// How failed requests are retried, shared by every eth_* call of the service
+#[marine]
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    // Rounds over the endpoint list after the first one
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+const ALLOWED_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+// Written by curl between the body and the HTTP status of the response
+const HTTP_STATUS_SEPARATOR: &str = "\n";
+
+// Exit code of curl aborting a download larger than its --max-filesize
+const CURL_FILESIZE_EXCEEDED: i32 = 63;
+
+// Linux caps a single command line argument at 128 KiB
+const MAX_INLINE_BODY_BYTES: usize = 64 * 1024;
+pub const REQUEST_BODY_DIR: &str = "/tmp/fevm_json_rpc";
+
+// Methods whose effect a retry after a timeout or a 5xx could repeat, a filter poll
+// moves the filter cursor so a resent one returns nothing for the lost changes
+const STATE_CHANGING_METHODS: [&str; 6] = [
+    "eth_sendRawTransaction",
+    "eth_sendTransaction",
+    "eth_newFilter",
+    "eth_newBlockFilter",
+    "eth_newPendingTransactionFilter",
+    "eth_getFilterChanges",
+];
+
+const DEFAULT_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_retries: 2,
+    initial_backoff_ms: 250,
+    max_backoff_ms: 4_000,
+};
+
+// Every retry is a full round over the endpoints, so a policy may not retry forever
+const MAX_RETRIES: u32 = 10;
+
+static RETRY_POLICY: Mutex<RetryPolicy> = Mutex::new(DEFAULT_RETRY_POLICY);
+
+// Keeps the spooled bodies of service instances sharing the mapped directory apart
+static SPOOL_PREFIX: OnceLock<String> = OnceLock::new();
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        DEFAULT_RETRY_POLICY
+    }
+}
+
+impl RetryPolicy {
+    fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(retry))
+            .min(self.max_backoff_ms);
+        Duration::from_millis(backoff)
+    }
+}
+
+#[marine]
+pub fn set_retry_policy(policy: RetryPolicy) -> EmptyResult {
+    ensure_service_owner(&get_call_parameters())
+        .and_then(|_| apply_retry_policy(policy))
+        .into()
+}
+
+fn apply_retry_policy(policy: RetryPolicy) -> Result<()> {
+    if policy.max_retries > MAX_RETRIES {
+        return Err(Error::InvalidConfig(format!(
+            "max_retries {} is above the limit of {}",
+            policy.max_retries, MAX_RETRIES
+        )));
+    }
+    if let Ok(mut current) = RETRY_POLICY.lock() {
+        *current = policy;
+    }
+    Ok(())
+}
+
+#[marine]
+pub fn get_retry_policy() -> RetryPolicy {
+    RETRY_POLICY
+        .lock()
+        .map(|policy| policy.clone())
+        .unwrap_or_default()
+}
+
+// Everything a curl run produced, the HTTP status is 0 when no response was received
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurlResponse {
+    pub http_status: u16,
+    pub exit_code: i32,
+    pub stderr: String,
+    pub body: String,
+}
+
+impl CurlResponse {
+    /**
+     * Split the status appended by `-w` off the body
+     */
+    pub fn from_mounted(result: MountedBinaryResult) -> Result<Self> {
+        let stdout = String::from_utf8(result.stdout)
+            .map_err(|err| Error::Transport(format!("curl output is not utf-8: {}", err)))?;
+        let mut stderr = String::from_utf8_lossy(&result.stderr).to_string();
+        if stderr.is_empty() {
+            stderr = result.error;
+        }
+
+        let (body, http_status) = match stdout.rsplit_once(HTTP_STATUS_SEPARATOR) {
+            Some((body, status)) => (body.to_string(), status.trim().parse().unwrap_or(0)),
+            None => (stdout, 0),
+        };
+
+        Ok(Self {
+            http_status,
+            exit_code: result.ret_code,
+            stderr,
+            body,
+        })
+    }
+
+    /**
+     * The response unless its body exceeds `max_bytes`, zero means no limit
+     */
+    pub fn within_limit(self, max_bytes: u64) -> Result<Self> {
+        if self.exit_code == CURL_FILESIZE_EXCEEDED {
+            return Err(Error::ResponseTooLarge {
+                size: 0,
+                limit: max_bytes,
+            });
+        }
+
+        let size = self.body.len() as u64;
+        if max_bytes > 0 && size > max_bytes {
+            return Err(Error::ResponseTooLarge {
+                size,
+                limit: max_bytes,
+            });
+        }
+        Ok(self)
+    }
+
+    /**
+     * The body of a successful response, or the curl / HTTP failure
+     */
+    pub fn into_body(self) -> Result<String> {
+        if self.exit_code != 0 {
+            return Err(Error::Curl {
+                exit_code: self.exit_code,
+                stderr: self.stderr,
+            });
+        }
+        if !(200..300).contains(&self.http_status) {
+            return Err(Error::Http {
+                status: self.http_status,
+                body: self.body,
+            });
+        }
+        if self.body.is_empty() {
+            return Err(Error::Transport("Curl connection failed".to_string()));
+        }
+        Ok(self.body)
+    }
+
+    /**
+     * Like `into_body`, but a JSON-RPC error object sent along a non-2xx status
+     * is reported as the JSON-RPC error it is
+     */
+    pub fn into_rpc_body(self) -> Result<String> {
+        match self.into_body() {
+            Err(Error::Http { status, body }) => match serde_json::from_str::<Response>(&body) {
+                Ok(Response {
+                    error: Some(err), ..
+                }) => Err(err.into()),
+                _ => Err(Error::Http { status, body }),
+            },
+            body => body,
+        }
+    }
+}
+
+// The outcome of a request along with the number of curl calls it took
+#[derive(Debug)]
+pub struct Delivery<T> {
+    pub result: Result<T>,
+    pub attempts: u32,
+}
+
+/**
+ * Split the `url` argument of the exports into its ordered list of endpoints.
+ * Several endpoints are given comma separated, e.g. "https://a,https://b"
+ */
+pub fn endpoints(url: &str) -> Vec<String> {
+    url.split(',')
+        .map(str::trim)
+        .filter(|endpoint| !endpoint.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/**
+ * Run `attempt` against every endpoint in order until one succeeds, backing off
+ * between rounds. Errors that another try cannot fix are returned straight away.
+ */
+pub fn with_retry<T>(url: &str, attempt: impl FnMut(&String) -> Result<T>) -> Delivery<T> {
+    retry(url, &get_retry_policy(), Error::is_retryable, attempt)
+}
+
+fn retry<T>(
+    url: &str,
+    policy: &RetryPolicy,
+    retryable: impl Fn(&Error) -> bool,
+    mut attempt: impl FnMut(&String) -> Result<T>,
+) -> Delivery<T> {
+    let endpoints = endpoints(url);
+    if endpoints.is_empty() {
+        return Delivery {
+            result: Err(Error::Transport("no endpoint url given".to_string())),
+            attempts: 0,
+        };
+    }
+
+    let mut attempts = 0;
+    let mut last_err = None;
+
+    for retry in 0..=policy.max_retries {
+        if retry > 0 {
+            std::thread::sleep(policy.backoff(retry - 1));
+        }
+
+        for endpoint in endpoints.iter() {
+            attempts += 1;
+            match attempt(endpoint) {
+                Err(err) if retryable(&err) => {
+                    log::info!(
+                        "attempt {} on {} failed: {}",
+                        attempts,
+                        redact_url(endpoint),
+                        err
+                    );
+                    last_err = Some(err);
+                }
+                result => return Delivery { result, attempts },
+            }
+        }
+    }
+
+    Delivery {
+        result: Err(last_err.unwrap_or_else(|| Error::Transport("no attempt made".to_string()))),
+        attempts,
+    }
+}
+
+pub fn send_request<T: DeserializeOwned>(url: &str, request: &Request) -> Delivery<T> {
+    let body = match request.body() {
+        Ok(body) => body,
+        Err(err) => {
+            return Delivery {
+                result: Err(err),
+                attempts: 0,
+            }
+        }
+    };
+    let spool_name = format!("request-{}", request.id);
+    let retryable = retry_rule([request.method.as_str()]);
+
+    retry(url, &get_retry_policy(), retryable, |endpoint| {
+        let res = post(&body, endpoint, &spool_name)?.into_rpc_body()?;
+        decode_response(&res, &request.id)
+    })
+}
+
+pub fn send_batch<T: DeserializeOwned>(
+    url: &str,
+    batch: &BatchRequest,
+) -> Delivery<Vec<Result<T>>> {
+    let body = match batch.body() {
+        Ok(body) => body,
+        Err(err) => {
+            return Delivery {
+                result: Err(err),
+                attempts: 0,
+            }
+        }
+    };
+    let ids = batch.ids();
+    let spool_name = format!(
+        "batch-{}",
+        ids.first().map(Id::to_string).unwrap_or_default()
+    );
+
+    let retryable = retry_rule(batch.requests.iter().map(|request| request.method.as_str()));
+
+    retry(url, &get_retry_policy(), retryable, |endpoint| {
+        let res = post(&body, endpoint, &spool_name)?.into_rpc_body()?;
+        decode_batch_response(&res, &ids)
+    })
+}
+
+/**
+ * Which errors requests of `methods` may be sent again after. A state changing
+ * request that may have reached the node is not sent again, e.g. a resent
+ * transaction fails with "already known" although it was broadcast.
+ */
+fn retry_rule<'a>(methods: impl IntoIterator<Item = &'a str>) -> fn(&Error) -> bool {
+    let changes_state = methods
+        .into_iter()
+        .any(|method| STATE_CHANGING_METHODS.contains(&method));
+
+    if changes_state {
+        Error::is_retryable_unsent
+    } else {
+        Error::is_retryable
+    }
+}
+
+/**
+ * POST a JSON body. Bodies too large for a command line argument are written to
+ * REQUEST_BODY_DIR and read back by curl, the directory has to be mapped into the
+ * module under the same path it has on the host.
+ */
+fn post(body: &str, url: &str, spool_name: &str) -> Result<CurlResponse> {
+    let mut curl_args = vec![
+        "-X".to_string(),
+        "POST".to_string(),
+        "-H".to_string(),
+        "Content-Type: application/json".to_string(),
+        "--data-binary".to_string(),
+    ];
+
+    // A JSON body starts with `{` or `[`, so curl never mistakes it for an `@file`
+    if body.len() <= MAX_INLINE_BODY_BYTES {
+        curl_args.push(body.to_string());
+        return curl_request_res(curl_args, url);
+    }
+
+    let path = spool_path(spool_name);
+    fs::create_dir_all(REQUEST_BODY_DIR)
+        .and_then(|_| fs::write(&path, body))
+        .map_err(|err| Error::Io(format!("cannot write request body to {}: {}", path, err)))?;
+
+    curl_args.push(format!("@{}", path));
+    let response = curl_request_res(curl_args, url);
+
+    if let Err(err) = fs::remove_file(&path) {
+        log::info!("cannot remove {}: {}", path, err);
+    }
+    response
+}
+
+fn spool_path(spool_name: &str) -> String {
+    let prefix = SPOOL_PREFIX.get_or_init(|| {
+        // The keys of a RandomState are drawn from the random source of the host
+        format!("{:016x}", RandomState::new().build_hasher().finish())
+    });
+    format!("{}/{}-{}.json", REQUEST_BODY_DIR, prefix, spool_name)
+}
+
+/**
+ * Only plain http(s) urls are handed to curl, anything curl could read as an
+ * option, a local file or a url glob is rejected
+ */
+pub fn validate_url(url: &str) -> Result<&str> {
+    let invalid = |reason: &str| Err(Error::InvalidUrl(format!("{:?} {}", url, reason)));
+
+    if url.starts_with('-') {
+        return invalid("looks like a curl option");
+    }
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return invalid("contains whitespace or control characters");
+    }
+    // Failover lists are passed comma separated, a comma would split the url
+    if url.contains(',') {
+        return invalid("contains a comma, which separates failover endpoints, write it as %2C");
+    }
+
+    let lowercase = url.to_ascii_lowercase();
+    let rest = match ALLOWED_SCHEMES
+        .iter()
+        .find_map(|scheme| lowercase.strip_prefix(scheme))
+    {
+        Some(rest) => rest,
+        None => return invalid("must start with http:// or https://"),
+    };
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if host.is_empty() || host.starts_with('-') || host.starts_with('@') {
+        return invalid("has no valid host");
+    }
+
+    // Brackets are only allowed around an IPv6 literal host such as [::1]
+    let path = &rest[host.len()..];
+    let host = match host.strip_prefix('[').and_then(|host| host.split_once(']')) {
+        Some((ip, port)) if is_ipv6_literal(ip) && (port.is_empty() || port.starts_with(':')) => {
+            port
+        }
+        _ => host,
+    };
+    if host.contains(['{', '}', '[', ']']) || path.contains(['{', '}', '[', ']']) {
+        return invalid("contains url glob characters");
+    }
+
+    Ok(url)
+}
+
+fn is_ipv6_literal(ip: &str) -> bool {
+    ip.contains(':')
+        && ip
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c == ':' || c == '.')
+}
+
+/**
+ * Build the full curl argument list, the url always comes last after `--`
+ */
+pub fn curl_command(
+    curl_args: Vec<String>,
+    url: &str,
+    config: &EndpointConfig,
+) -> Result<Vec<String>> {
+    let url = validate_url(url)?;
+
+    let mut curl_cmd = vec![
+        "-sS".to_string(),
+        "-w".to_string(),
+        format!("{}%{{http_code}}", HTTP_STATUS_SEPARATOR),
+        "--proto".to_string(),
+        "=https,http".to_string(),
+        "--proto-redir".to_string(),
+        "=https,http".to_string(),
+        "--globoff".to_string(),
+    ];
+    curl_cmd.extend(curl_args);
+    curl_cmd.extend(config.curl_options());
+    curl_cmd.push("--".to_string());
+    curl_cmd.push(url.to_string());
+
+    Ok(curl_cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn accepts_http_and_https_urls() {
+        assert!(validate_url("https://api.node.glif.io/rpc/v1").is_ok());
+        assert!(validate_url("http://127.0.0.1:1234/rpc/v1").is_ok());
+        assert!(validate_url("HTTPS://example.com?key=value#fragment").is_ok());
+        assert!(validate_url("http://[::1]:1234/rpc/v1").is_ok());
+        assert!(validate_url("https://[2001:db8::7]/rpc/v1").is_ok());
+    }
+
+    #[test]
+    fn rejects_curl_options() {
+        for url in ["-o /tmp/pwned", "--output=/tmp/pwned", "-K/etc/passwd", "-"] {
+            assert!(
+                matches!(validate_url(url), Err(Error::InvalidUrl(_))),
+                "{} was accepted",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_other_schemes() {
+        for url in [
+            "file:///etc/passwd",
+            "FILE:///etc/passwd",
+            "ftp://example.com/abi.json",
+            "gopher://127.0.0.1:25/",
+            "dict://127.0.0.1:11211/",
+            "scp://host/file",
+            "example.com/abi.json",
+            "",
+        ] {
+            assert!(
+                matches!(validate_url(url), Err(Error::InvalidUrl(_))),
+                "{} was accepted",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_injected_arguments_and_globs() {
+        for url in [
+            "https://example.com -o /tmp/pwned",
+            "https://example.com\n-o/tmp/pwned",
+            "https://example.com\t--output",
+            "https://example.com/{a,b}",
+            "https://example.com/[1-1000]",
+            "https://[1-1000].example.com/",
+            "https://[::1]x/rpc/v1",
+            "https://[::1]:1234/[1-2]",
+            "https://",
+            "https:///etc/passwd",
+            "https://-o/tmp",
+            "https://rpc.example.com/v1?chains=1,314",
+        ] {
+            assert!(
+                matches!(validate_url(url), Err(Error::InvalidUrl(_))),
+                "{:?} was accepted",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn url_always_follows_the_separator() {
+        let curl_args = vec!["-s".to_string()];
+        let curl_cmd =
+            curl_command(curl_args, "https://example.com", &EndpointConfig::default()).unwrap();
+
+        assert_eq!(curl_cmd[curl_cmd.len() - 2], "--");
+        assert_eq!(curl_cmd[curl_cmd.len() - 1], "https://example.com");
+        assert!(curl_cmd
+            .windows(2)
+            .any(|w| w[0] == "--proto" && w[1] == "=https,http"));
+    }
+
+    #[test]
+    fn invalid_url_never_builds_a_command() {
+        let err =
+            curl_command(Vec::new(), "-o /tmp/pwned", &EndpointConfig::default()).unwrap_err();
+
+        assert_eq!(err.code(), "invalid_url");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn every_endpoint_of_a_list_is_validated() {
+        let delivery = with_retry("https://example.com,-o /tmp/pwned", |endpoint| {
+            validate_url(endpoint).map(str::to_string)
+        });
+
+        assert_eq!(delivery.result.unwrap(), "https://example.com");
+
+        let delivery = with_retry("file:///etc/passwd,https://example.com", |endpoint| {
+            validate_url(endpoint).map(str::to_string)
+        });
+
+        assert!(matches!(delivery.result, Err(Error::InvalidUrl(_))));
+        assert_eq!(delivery.attempts, 1);
+    }
+
+    const NO_BACKOFF: RetryPolicy = RetryPolicy {
+        max_retries: 2,
+        initial_backoff_ms: 0,
+        max_backoff_ms: 0,
+    };
+
+    fn timeout() -> Error {
+        Error::Curl {
+            exit_code: 28,
+            stderr: "curl: (28) Operation timed out".to_string(),
+        }
+    }
+
+    #[test]
+    fn retryable_errors_fail_over_to_the_next_endpoint() {
+        let mut tried = Vec::new();
+        let delivery = retry(
+            "https://a,https://b",
+            &NO_BACKOFF,
+            Error::is_retryable,
+            |endpoint| {
+                tried.push(endpoint.clone());
+                match endpoint.as_str() {
+                    "https://a" => Err(timeout()),
+                    _ => Ok(endpoint.clone()),
+                }
+            },
+        );
+
+        assert_eq!(delivery.result.unwrap(), "https://b");
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(tried, ["https://a", "https://b"]);
+    }
+
+    #[test]
+    fn attempts_are_counted_across_rounds() {
+        let delivery = retry(
+            "https://a,https://b",
+            &NO_BACKOFF,
+            Error::is_retryable,
+            |_| Err::<(), _>(timeout()),
+        );
+
+        assert_eq!(delivery.result.unwrap_err(), timeout());
+        assert_eq!(delivery.attempts, 6);
+
+        let mut calls = 0;
+        let delivery = retry("https://a", &NO_BACKOFF, Error::is_retryable, |_| {
+            calls += 1;
+            match calls {
+                3 => Ok(calls),
+                _ => Err(timeout()),
+            }
+        });
+
+        assert_eq!(delivery.result.unwrap(), 3);
+        assert_eq!(delivery.attempts, 3);
+    }
+
+    #[test]
+    fn unbounded_retry_policies_are_rejected() {
+        let policy = RetryPolicy {
+            max_retries: u32::MAX,
+            ..DEFAULT_RETRY_POLICY
+        };
+
+        assert!(matches!(
+            apply_retry_policy(policy),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert_eq!(
+            get_retry_policy().max_retries,
+            DEFAULT_RETRY_POLICY.max_retries
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff_ms: 250,
+            max_backoff_ms: 1_000,
+        };
+        let backoffs: Vec<u64> = (0..5)
+            .map(|retry| policy.backoff(retry).as_millis() as u64)
+            .collect();
+
+        assert_eq!(backoffs, [250, 500, 1_000, 1_000, 1_000]);
+        assert_eq!(policy.backoff(u32::MAX).as_millis(), 1_000);
+    }
+
+    #[test]
+    fn state_changing_requests_are_not_resent_once_they_may_have_arrived() {
+        let delivery = retry(
+            "https://a,https://b",
+            &NO_BACKOFF,
+            Error::is_retryable_unsent,
+            |_| Err::<(), _>(timeout()),
+        );
+        assert_eq!(delivery.attempts, 1);
+
+        let refused = Error::Curl {
+            exit_code: 7,
+            stderr: "curl: (7) Failed to connect".to_string(),
+        };
+        let delivery = retry(
+            "https://a,https://b",
+            &NO_BACKOFF,
+            Error::is_retryable_unsent,
+            |endpoint| match endpoint.as_str() {
+                "https://a" => Err(refused.clone()),
+                _ => Ok(()),
+            },
+        );
+        assert!(delivery.result.is_ok());
+        assert_eq!(delivery.attempts, 2);
+    }
+
+    #[test]
+    fn spool_files_are_prefixed_per_instance() {
+        let path = spool_path("request-1");
+        let name = path.strip_prefix(REQUEST_BODY_DIR).unwrap();
+
+        assert_eq!(path, spool_path("request-1"));
+        assert!(name.ends_with("-request-1.json"));
+        assert_eq!(name.len(), "/".len() + 16 + "-request-1.json".len());
+    }
+
+    #[test]
+    fn batches_with_a_state_changing_request_are_not_resent() {
+        let batch = BatchRequest::new(vec![
+            Request::new("eth_blockNumber".to_string(), json!([]), 1),
+            Request::new("eth_sendRawTransaction".to_string(), json!(["0x02"]), 2),
+        ]);
+        let retryable = retry_rule(batch.requests.iter().map(|request| request.method.as_str()));
+        let delivery = retry("https://a,https://b", &NO_BACKOFF, retryable, |_| {
+            Err::<(), _>(timeout())
+        });
+        assert_eq!(delivery.attempts, 1);
+
+        let retryable = retry_rule(["eth_blockNumber", "eth_chainId"]);
+        let delivery = retry("https://a,https://b", &NO_BACKOFF, retryable, |_| {
+            Err::<(), _>(timeout())
+        });
+        assert_eq!(delivery.attempts, 6);
+    }
+
+    #[test]
+    fn timed_out_filter_polls_are_not_resent() {
+        let retryable = retry_rule(["eth_getFilterChanges"]);
+        let delivery = retry("https://a,https://b", &NO_BACKOFF, retryable, |_| {
+            Err::<(), _>(timeout())
+        });
+
+        assert_eq!(delivery.attempts, 1);
+    }
+
+    #[test]
+    fn unwritable_spool_file_is_not_retried() {
+        let delivery = retry(
+            "https://a,https://b",
+            &NO_BACKOFF,
+            Error::is_retryable,
+            |_| Err::<(), _>(Error::Io("cannot write request body".to_string())),
+        );
+
+        assert_eq!(delivery.result.unwrap_err().code(), "io");
+        assert_eq!(delivery.attempts, 1);
+    }
+
+    fn mounted(ret_code: i32, stdout: &str, stderr: &str) -> MountedBinaryResult {
+        MountedBinaryResult {
+            ret_code,
+            error: "".to_string(),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn http_status_is_split_from_the_body() {
+        let response =
+            CurlResponse::from_mounted(mounted(0, "{\"result\":\"0x1\"}\n200", "")).unwrap();
+
+        assert_eq!(response.http_status, 200);
+        assert_eq!(response.body, "{\"result\":\"0x1\"}");
+        assert_eq!(response.into_body().unwrap(), "{\"result\":\"0x1\"}");
+    }
+
+    #[test]
+    fn curl_failures_keep_their_exit_code() {
+        let response = CurlResponse::from_mounted(mounted(
+            6,
+            "\n000",
+            "curl: (6) Could not resolve host: nope.invalid",
+        ))
+        .unwrap();
+        let err = response.into_body().unwrap_err();
+
+        assert_eq!(err.code(), "curl");
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("Could not resolve host"));
+    }
+
+    #[test]
+    fn oversized_response_is_attempted_once() {
+        let delivery = retry(
+            "https://a,https://b",
+            &NO_BACKOFF,
+            Error::is_retryable,
+            |_| {
+                CurlResponse::from_mounted(mounted(0, &format!("{}\n200", "x".repeat(64)), ""))?
+                    .within_limit(32)
+            },
+        );
+
+        assert_eq!(
+            delivery.result.unwrap_err(),
+            Error::ResponseTooLarge {
+                size: 64,
+                limit: 32
+            }
+        );
+        assert_eq!(delivery.attempts, 1);
+
+        let aborted = CurlResponse::from_mounted(mounted(
+            63,
+            "\n200",
+            "curl: (63) Maximum file size exceeded",
+        ))
+        .unwrap()
+        .within_limit(32)
+        .unwrap_err();
+        assert_eq!(aborted.code(), "response_too_large");
+        assert_eq!(aborted.to_string(), "response exceeds the 32 bytes limit");
+
+        let response = CurlResponse::from_mounted(mounted(0, "{}\n200", "")).unwrap();
+        assert!(response.clone().within_limit(0).is_ok());
+        assert!(response.within_limit(2).is_ok());
+    }
+
+    #[test]
+    fn http_errors_are_classified() {
+        let rate_limited =
+            CurlResponse::from_mounted(mounted(0, "<html>Too Many Requests</html>\n429", ""))
+                .unwrap()
+                .into_rpc_body()
+                .unwrap_err();
+        assert!(matches!(rate_limited, Error::Http { status: 429, .. }));
+        assert!(rate_limited.is_retryable());
+
+        let not_found = CurlResponse::from_mounted(mounted(0, "not found\n404", ""))
+            .unwrap()
+            .into_rpc_body()
+            .unwrap_err();
+        assert!(matches!(not_found, Error::Http { status: 404, .. }));
+        assert!(!not_found.is_retryable());
+    }
+
+    #[test]
+    fn rpc_error_sent_with_http_error_status_is_kept() {
+        let body = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"limit exceeded"}}"#;
+        let err = CurlResponse::from_mounted(mounted(0, &format!("{}\n503", body), ""))
+            .unwrap()
+            .into_rpc_body()
+            .unwrap_err();
+
+        assert!(matches!(err, Error::JsonRpc { code: -32005, .. }));
+        assert!(err.is_retryable());
+    }
+}
@@ -1,8 +1,11 @@
 use crate::bytes_type::Bytes;
-use ethereum_types::{H160, U256};
+use crate::error::{Error, Result};
+use crate::eth_utils::{normalize_hex_word, parse_hex_u64, strip_hex_prefix, BlockId};
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core as rpc;
 use marine_rs_sdk::marine;
 use serde::{Deserialize, Serialize};
-use crate::eth_utils::{hex_to_decimal};
+use std::collections::BTreeMap;
 
 #[marine]
 #[derive(Debug, Default)]
@@ -19,20 +22,111 @@ pub struct Tx {
     pub transaction_index: String,
     pub value: String,
     pub logs: Vec<TxLog>,
+    pub tx_type: String,
+    pub chain_id: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    pub access_list: Vec<AccessListItem>,
+    pub v: String,
+    pub r: String,
+    pub s: String,
+    // true while the transaction is not included in a block
+    pub pending: bool,
 }
 
 #[marine]
 #[derive(Debug, Default, Clone)]
+pub struct AccessListItem {
+    pub address: String,
+    pub storage_keys: Vec<String>,
+}
+
+#[marine]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TxLog {
+    pub address: String,
     pub topics: Vec<String>,
     pub data: String,
     pub transaction_hash: String,
+    pub transaction_index: u64,
+    pub block_hash: String,
     pub block_number: u64,
+    pub log_index: u64,
+    pub removed: bool,
+}
+
+#[marine]
+#[derive(Debug, Default)]
+pub struct TxReceipt {
+    pub transaction_hash: String,
+    pub transaction_index: String,
+    pub block_hash: String,
+    pub block_number: String,
+    pub from: String,
+    pub to: String,
+    pub cumulative_gas_used: String,
+    pub gas_used: String,
+    pub effective_gas_price: String,
+    pub contract_address: String,
+    pub logs: Vec<TxLog>,
+    pub logs_bloom: String,
+    pub tx_type: String,
+    // 0x1 when the transaction succeeded, 0x0 when it reverted
+    pub status: String,
+}
+
+// Block header, quantities are kept as the node's hex strings like in `Tx`
+#[marine]
+#[derive(Debug, Default)]
+pub struct Block {
+    pub number: String,
+    pub hash: String,
+    pub parent_hash: String,
+    pub timestamp: String,
+    pub miner: String,
+    pub gas_limit: String,
+    pub gas_used: String,
+    // Empty before London
+    pub base_fee_per_gas: String,
+    pub state_root: String,
+    pub receipts_root: String,
+    pub transactions_root: String,
+    pub logs_bloom: String,
 }
 
 #[derive(Debug, Default, Deserialize)]
-pub struct ResultSerde {
-    pub transactions: Vec<TxSerde>,
+#[serde(rename_all = "camelCase")]
+pub struct BlockSerde {
+    // number, hash: null when it's a pending block.
+    pub number: Option<String>,
+    pub hash: Option<String>,
+    pub parent_hash: Option<String>,
+    pub timestamp: Option<String>,
+    pub miner: Option<String>,
+    pub gas_limit: Option<String>,
+    pub gas_used: Option<String>,
+    pub base_fee_per_gas: Option<String>,
+    pub state_root: Option<String>,
+    pub receipts_root: Option<String>,
+    pub transactions_root: Option<String>,
+    pub logs_bloom: Option<String>,
+
+    #[serde(default)]
+    pub transactions: BlockTransactionsSerde,
+}
+
+// transactions: full objects when the block was requested hydrated, hashes otherwise.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BlockTransactionsSerde {
+    Hashes(Vec<String>),
+    Full(Vec<TxSerde>),
+}
+
+impl Default for BlockTransactionsSerde {
+    fn default() -> Self {
+        BlockTransactionsSerde::Hashes(Vec::new())
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -73,27 +167,114 @@ pub struct TxSerde {
 
     // // value: QUANTITY - value transferred in Wei.
     pub value: Option<String>,
+
+    // // type: QUANTITY - 0x0 legacy, 0x1 access list (EIP-2930), 0x2 dynamic fee (EIP-1559).
+    #[serde(rename = "type")]
+    pub tx_type: Option<String>,
+
+    #[serde(rename = "chainId")]
+    pub chain_id: Option<String>,
+
+    #[serde(rename = "maxFeePerGas")]
+    pub max_fee_per_gas: Option<String>,
+
+    #[serde(rename = "maxPriorityFeePerGas")]
+    pub max_priority_fee_per_gas: Option<String>,
+
+    #[serde(rename = "accessList")]
+    pub access_list: Option<Vec<AccessListItemSerde>>,
+
+    // // v, r, s: signature values, v is the y parity for typed transactions.
+    pub v: Option<String>,
+    pub r: Option<String>,
+    pub s: Option<String>,
 }
 
 fn empty_vector() -> Vec<TxSerdeLogs> {
     Vec::new()
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct AccessListItemSerde {
+    pub address: Option<String>,
+
+    #[serde(rename = "storageKeys", default)]
+    pub storage_keys: Vec<String>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct TxSerdeLogs {
+    pub address: Option<String>,
     pub topics: Option<Vec<String>>,
     pub data: Option<String>,
 
     #[serde(rename = "transactionHash")]
     pub transaction_hash: Option<String>,
 
+    #[serde(rename = "transactionIndex")]
+    pub transaction_index: Option<String>,
+
+    #[serde(rename = "blockHash")]
+    pub block_hash: Option<String>,
+
     #[serde(rename = "blockNumber")]
     pub block_number: Option<String>,
+
+    #[serde(rename = "logIndex")]
+    pub log_index: Option<String>,
+
+    // removed: true when the log was removed due to a chain reorganization.
+    #[serde(default)]
+    pub removed: bool,
 }
 
-impl From<&TxSerde> for Tx {
-    fn from(ser: &TxSerde) -> Self {
-        Self {
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxReceiptSerde {
+    pub transaction_hash: Option<String>,
+    pub transaction_index: Option<String>,
+    pub block_hash: Option<String>,
+    pub block_number: Option<String>,
+    pub from: Option<String>,
+
+    // to: null when it's a contract creation transaction.
+    pub to: Option<String>,
+
+    // cumulativeGasUsed: gas used by this and all preceding transactions in the block.
+    pub cumulative_gas_used: Option<String>,
+    pub gas_used: Option<String>,
+
+    // effectiveGasPrice: price per gas actually paid, base fee plus tip.
+    pub effective_gas_price: Option<String>,
+
+    // contractAddress: the contract created, null when not a contract creation.
+    pub contract_address: Option<String>,
+
+    #[serde(default = "empty_vector")]
+    pub logs: Vec<TxSerdeLogs>,
+    pub logs_bloom: Option<String>,
+
+    #[serde(rename = "type")]
+    pub tx_type: Option<String>,
+    pub status: Option<String>,
+}
+
+/***
+ * Parse an optional hex quantity, a missing value (e.g. a pending log) is 0
+ */
+fn parse_optional_quantity(quantity: &Option<String>) -> Result<u64> {
+    quantity
+        .as_deref()
+        .map(parse_hex_u64)
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+impl TryFrom<&TxSerde> for Tx {
+    type Error = Error;
+
+    fn try_from(ser: &TxSerde) -> Result<Self> {
+        Ok(Self {
             block_hash: ser.block_hash.clone().unwrap_or_default(),
             block_number: ser.block_number.clone().unwrap_or_default(),
             from: ser.from.clone().unwrap_or_default(),
@@ -108,13 +289,51 @@ impl From<&TxSerde> for Tx {
             logs: ser
                 .logs
                 .iter()
-                .map(|log| TxLog {
-                    topics: log.topics.clone().unwrap_or_default(),
-                    data: log.data.clone().unwrap_or_default(),
-                    transaction_hash: log.transaction_hash.clone().unwrap_or_default(),
-                    block_number: hex_to_decimal(log.block_number.clone().unwrap_or_default()),
-                })
+                .cloned()
+                .map(TxLog::try_from)
+                .collect::<Result<_>>()?,
+            tx_type: ser.tx_type.clone().unwrap_or_default(),
+            chain_id: ser.chain_id.clone().unwrap_or_default(),
+            max_fee_per_gas: ser.max_fee_per_gas.clone().unwrap_or_default(),
+            max_priority_fee_per_gas: ser.max_priority_fee_per_gas.clone().unwrap_or_default(),
+            access_list: ser
+                .access_list
+                .iter()
+                .flatten()
+                .map(AccessListItem::from)
                 .collect(),
+            v: ser.v.clone().unwrap_or_default(),
+            r: ser.r.clone().unwrap_or_default(),
+            s: ser.s.clone().unwrap_or_default(),
+            pending: ser.block_hash.is_none(),
+        })
+    }
+}
+
+impl From<&AccessListItemSerde> for AccessListItem {
+    fn from(ser: &AccessListItemSerde) -> Self {
+        Self {
+            address: ser.address.clone().unwrap_or_default(),
+            storage_keys: ser.storage_keys.clone(),
+        }
+    }
+}
+
+impl From<&BlockSerde> for Block {
+    fn from(ser: &BlockSerde) -> Self {
+        Self {
+            number: ser.number.clone().unwrap_or_default(),
+            hash: ser.hash.clone().unwrap_or_default(),
+            parent_hash: ser.parent_hash.clone().unwrap_or_default(),
+            timestamp: ser.timestamp.clone().unwrap_or_default(),
+            miner: ser.miner.clone().unwrap_or_default(),
+            gas_limit: ser.gas_limit.clone().unwrap_or_default(),
+            gas_used: ser.gas_used.clone().unwrap_or_default(),
+            base_fee_per_gas: ser.base_fee_per_gas.clone().unwrap_or_default(),
+            state_root: ser.state_root.clone().unwrap_or_default(),
+            receipts_root: ser.receipts_root.clone().unwrap_or_default(),
+            transactions_root: ser.transactions_root.clone().unwrap_or_default(),
+            logs_bloom: ser.logs_bloom.clone().unwrap_or_default(),
         }
     }
 }
@@ -122,22 +341,163 @@ impl From<&TxSerde> for Tx {
 /***
  * Parse Logs data from respond to TxLog
  */
-impl From<TxSerdeLogs> for TxLog {
-    fn from(ser: TxSerdeLogs) -> Self {
-        Self {
-            transaction_hash: ser.transaction_hash.clone().unwrap_or_default(),
-            topics: ser.topics.clone().unwrap_or_default(),
-            data: ser.data.clone().unwrap_or_default(),
-            block_number: hex_to_decimal(ser.block_number.clone().unwrap_or_default()),
-        }
+impl TryFrom<TxSerdeLogs> for TxLog {
+    type Error = Error;
+
+    fn try_from(ser: TxSerdeLogs) -> Result<Self> {
+        Ok(Self {
+            block_number: parse_optional_quantity(&ser.block_number)?,
+            transaction_index: parse_optional_quantity(&ser.transaction_index)?,
+            log_index: parse_optional_quantity(&ser.log_index)?,
+            address: ser.address.unwrap_or_default(),
+            transaction_hash: ser.transaction_hash.unwrap_or_default(),
+            block_hash: ser.block_hash.unwrap_or_default(),
+            topics: ser.topics.unwrap_or_default(),
+            data: ser.data.unwrap_or_default(),
+            removed: ser.removed,
+        })
+    }
+}
+
+impl TryFrom<TxReceiptSerde> for TxReceipt {
+    type Error = Error;
+
+    fn try_from(ser: TxReceiptSerde) -> Result<Self> {
+        Ok(Self {
+            transaction_hash: ser.transaction_hash.unwrap_or_default(),
+            transaction_index: ser.transaction_index.unwrap_or_default(),
+            block_hash: ser.block_hash.unwrap_or_default(),
+            block_number: ser.block_number.unwrap_or_default(),
+            from: ser.from.unwrap_or_default(),
+            to: ser.to.unwrap_or_default(),
+            cumulative_gas_used: ser.cumulative_gas_used.unwrap_or_default(),
+            gas_used: ser.gas_used.unwrap_or_default(),
+            effective_gas_price: ser.effective_gas_price.unwrap_or_default(),
+            contract_address: ser.contract_address.unwrap_or_default(),
+            logs: ser
+                .logs
+                .into_iter()
+                .map(TxLog::try_from)
+                .collect::<Result<_>>()?,
+            logs_bloom: ser.logs_bloom.unwrap_or_default(),
+            tx_type: ser.tx_type.unwrap_or_default(),
+            status: ser.status.unwrap_or_default(),
+        })
     }
 }
 
-// ABI
+// One call of a JSON-RPC batch, `params` is the JSON text of the params array
 #[marine]
-#[derive(Debug, Deserialize)]
-pub struct Abi {
+#[derive(Debug, Clone)]
+pub struct BatchCall {
     pub method: String,
+    pub params: String,
+}
+
+// A call as given to eth_call and eth_estimateGas, empty fields are left out
+// of the request, quantities and data are hex strings
+#[marine]
+#[derive(Debug, Default, Clone)]
+pub struct CallRequest {
+    pub from: String,
+    pub to: String,
+    pub gas: String,
+    pub gas_price: String,
+    pub value: String,
+    pub data: String,
+}
+
+// Logs to look for, empty fields match anything. `topics` holds the accepted
+// values of each position in order, an empty position matches any topic.
+// `block_hash` restricts to one block and can't be combined with a range.
+#[marine]
+#[derive(Debug, Default, Clone)]
+pub struct LogFilter {
+    pub addresses: Vec<String>,
+    pub from_block: String,
+    pub to_block: String,
+    pub block_hash: String,
+    pub topics: Vec<Vec<String>>,
+}
+
+#[marine]
+#[derive(Debug, Default, Clone)]
+pub struct StorageSlot {
+    pub slot: String,
+    pub value: String,
+}
+
+// Account state a call is simulated against, empty fields keep the real state
+#[marine]
+#[derive(Debug, Default, Clone)]
+pub struct AccountOverride {
+    pub address: String,
+    pub balance: String,
+    pub nonce: String,
+    pub code: String,
+    pub state: Vec<StorageSlot>,
+    // Sent as `state`, wiping every other slot, rather than `stateDiff`
+    pub replace_state: bool,
+}
+
+// Header fields of the block a call is simulated in, empty fields keep the real ones
+#[marine]
+#[derive(Debug, Default, Clone)]
+pub struct BlockOverrides {
+    pub number: String,
+    pub time: String,
+    pub gas_limit: String,
+    pub fee_recipient: String,
+    pub prev_randao: String,
+    pub base_fee_per_gas: String,
+}
+
+#[marine]
+#[derive(Debug, Default, Clone)]
+pub struct CallOverrides {
+    pub accounts: Vec<AccountOverride>,
+    pub block: BlockOverrides,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountOverrideSerde {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    balance: Option<U256>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nonce: Option<U256>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<Bytes>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<BTreeMap<String, String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_diff: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockOverridesSerde {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    number: Option<U256>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time: Option<U256>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_limit: Option<U256>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fee_recipient: Option<H160>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prev_randao: Option<H256>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base_fee_per_gas: Option<U256>,
 }
 
 #[derive(Default, Serialize)]
@@ -161,3 +521,371 @@ pub struct TxCall {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Bytes>,
 }
+
+fn parse_optional_address(address: &str) -> Result<Option<H160>> {
+    if address.is_empty() {
+        return Ok(None);
+    }
+    let bytes = hex::decode(strip_hex_prefix(address)?)?;
+    if bytes.len() != 20 {
+        return Err(Error::Hex(format!(
+            "{:?} is not a 20 byte address",
+            address
+        )));
+    }
+    Ok(Some(H160::from_slice(&bytes)))
+}
+
+fn parse_optional_u256(quantity: &str) -> Result<Option<U256>> {
+    if quantity.is_empty() {
+        return Ok(None);
+    }
+    U256::from_str_radix(strip_hex_prefix(quantity)?, 16)
+        .map(Some)
+        .map_err(|err| Error::Hex(format!("{:?} is not a quantity: {}", quantity, err)))
+}
+
+impl TryFrom<&CallRequest> for TxCall {
+    type Error = Error;
+
+    fn try_from(call: &CallRequest) -> Result<Self> {
+        let data = parse_optional_bytes(&call.data)?;
+
+        Ok(Self {
+            from: parse_optional_address(&call.from)?,
+            to: parse_optional_address(&call.to)?,
+            gas: parse_optional_u256(&call.gas)?,
+            gas_price: parse_optional_u256(&call.gas_price)?,
+            value: parse_optional_u256(&call.value)?,
+            data,
+        })
+    }
+}
+
+fn parse_optional_bytes(data: &str) -> Result<Option<Bytes>> {
+    if data.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(hex::decode(strip_hex_prefix(data)?)?.into()))
+}
+
+impl TryFrom<&AccountOverride> for AccountOverrideSerde {
+    type Error = Error;
+
+    fn try_from(account: &AccountOverride) -> Result<Self> {
+        let state = account
+            .state
+            .iter()
+            .map(|slot| {
+                Ok((
+                    normalize_hex_word(&slot.slot)?,
+                    normalize_hex_word(&slot.value)?,
+                ))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        let (state, state_diff) = match (account.replace_state, state.is_empty()) {
+            (true, _) => (Some(state), None),
+            (false, true) => (None, None),
+            (false, false) => (None, Some(state)),
+        };
+
+        Ok(Self {
+            balance: parse_optional_u256(&account.balance)?,
+            nonce: parse_optional_u256(&account.nonce)?,
+            code: parse_optional_bytes(&account.code)?,
+            state,
+            state_diff,
+        })
+    }
+}
+
+impl TryFrom<&BlockOverrides> for BlockOverridesSerde {
+    type Error = Error;
+
+    fn try_from(block: &BlockOverrides) -> Result<Self> {
+        let prev_randao = if block.prev_randao.is_empty() {
+            None
+        } else {
+            let word = hex::decode(strip_hex_prefix(&normalize_hex_word(&block.prev_randao)?)?)?;
+            Some(H256::from_slice(&word))
+        };
+
+        Ok(Self {
+            number: parse_optional_u256(&block.number)?,
+            time: parse_optional_u256(&block.time)?,
+            gas_limit: parse_optional_u256(&block.gas_limit)?,
+            fee_recipient: parse_optional_address(&block.fee_recipient)?,
+            prev_randao,
+            base_fee_per_gas: parse_optional_u256(&block.base_fee_per_gas)?,
+        })
+    }
+}
+
+impl CallOverrides {
+    /**
+     * eth_call params following the block: the state overrides, then the block
+     * overrides when any is set. Nothing when there is nothing to override.
+     */
+    pub fn params(&self) -> Result<Vec<rpc::Value>> {
+        let mut accounts = BTreeMap::new();
+        for account in self.accounts.iter() {
+            let address = parse_optional_address(&account.address)?.ok_or_else(|| {
+                Error::InvalidArgument("account override has no address".to_string())
+            })?;
+            let account_serde = AccountOverrideSerde::try_from(account)?;
+            if accounts
+                .insert(format!("{:?}", address), account_serde)
+                .is_some()
+            {
+                return Err(Error::InvalidArgument(format!(
+                    "account {:?} is overridden twice",
+                    address
+                )));
+            }
+        }
+        let block = BlockOverridesSerde::try_from(&self.block)?;
+
+        let mut params = Vec::new();
+        let block = serde_json::to_value(block)?;
+        let has_block = block.as_object().is_some_and(|block| !block.is_empty());
+        if !accounts.is_empty() || has_block {
+            params.push(serde_json::to_value(accounts)?);
+        }
+        if has_block {
+            params.push(block);
+        }
+        Ok(params)
+    }
+}
+
+fn parse_topic(topic: &str) -> Result<H256> {
+    let bytes = hex::decode(strip_hex_prefix(topic)?)?;
+    if bytes.len() != 32 {
+        return Err(Error::Hex(format!("{:?} is not a 32 byte topic", topic)));
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+impl LogFilter {
+    /**
+     * Filter object of eth_getLogs and eth_newFilter
+     */
+    pub fn param(&self) -> Result<rpc::Value> {
+        let mut filter = serde_json::Map::new();
+
+        if !self.block_hash.is_empty() {
+            if !self.from_block.is_empty() || !self.to_block.is_empty() {
+                return Err(Error::InvalidArgument(
+                    "a log filter takes either a block hash or a block range".to_string(),
+                ));
+            }
+            let block_hash = self.block_hash.parse::<BlockId>()?.hash_param()?;
+            filter.insert("blockHash".to_string(), block_hash);
+        }
+        if !self.from_block.is_empty() {
+            let from_block = self.from_block.parse::<BlockId>()?.number_param()?;
+            filter.insert("fromBlock".to_string(), from_block);
+        }
+        if !self.to_block.is_empty() {
+            let to_block = self.to_block.parse::<BlockId>()?.number_param()?;
+            filter.insert("toBlock".to_string(), to_block);
+        }
+
+        if !self.addresses.is_empty() {
+            let addresses = self
+                .addresses
+                .iter()
+                .map(|address| {
+                    parse_optional_address(address)?.ok_or_else(|| {
+                        Error::InvalidArgument("log filter has an empty address".to_string())
+                    })
+                })
+                .collect::<Result<Vec<H160>>>()?;
+            filter.insert("address".to_string(), serde_json::to_value(addresses)?);
+        }
+
+        if !self.topics.is_empty() {
+            let topics = self
+                .topics
+                .iter()
+                .map(|alternatives| {
+                    let alternatives = alternatives
+                        .iter()
+                        .map(|topic| parse_topic(topic))
+                        .collect::<Result<Vec<H256>>>()?;
+                    // An empty position is a wildcard
+                    Ok(match alternatives.len() {
+                        0 => rpc::Value::Null,
+                        _ => serde_json::to_value(alternatives)?,
+                    })
+                })
+                .collect::<Result<Vec<rpc::Value>>>()?;
+            filter.insert("topics".to_string(), rpc::Value::Array(topics));
+        }
+
+        Ok(rpc::Value::Object(filter))
+    }
+}
+
+// Fees of a range of blocks, `base_fee_per_gas` has one more entry than
+// blocks in the range: the base fee of the block after the newest one
+#[marine]
+#[derive(Debug, Default)]
+pub struct FeeHistory {
+    pub oldest_block: u64,
+    pub base_fee_per_gas: Vec<u64>,
+    pub gas_used_ratio: Vec<f64>,
+    // Priority fees paid in each block at the requested percentiles
+    pub reward: Vec<Vec<u64>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistorySerde {
+    pub oldest_block: Option<String>,
+
+    #[serde(default)]
+    pub base_fee_per_gas: Vec<String>,
+
+    #[serde(default)]
+    pub gas_used_ratio: Vec<f64>,
+
+    // reward: left out when no percentiles were requested.
+    #[serde(default)]
+    pub reward: Vec<Vec<String>>,
+}
+
+impl TryFrom<FeeHistorySerde> for FeeHistory {
+    type Error = Error;
+
+    fn try_from(ser: FeeHistorySerde) -> Result<Self> {
+        Ok(Self {
+            oldest_block: parse_optional_quantity(&ser.oldest_block)?,
+            base_fee_per_gas: ser
+                .base_fee_per_gas
+                .iter()
+                .map(|fee| parse_hex_u64(fee))
+                .collect::<Result<_>>()?,
+            gas_used_ratio: ser.gas_used_ratio,
+            reward: ser
+                .reward
+                .iter()
+                .map(|rewards| rewards.iter().map(|fee| parse_hex_u64(fee)).collect())
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn overrides_are_serialized_per_address() {
+        let account = "0x00000000000000000000000000000000000000aa";
+        let overrides = CallOverrides {
+            accounts: vec![AccountOverride {
+                address: account.to_string(),
+                balance: "0xde0b6b3a7640000".to_string(),
+                state: vec![StorageSlot {
+                    slot: "0x0".to_string(),
+                    value: "0x1".to_string(),
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let params = overrides.params().unwrap();
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0][account]["balance"], "0xde0b6b3a7640000");
+        assert_eq!(
+            params[0][account]["stateDiff"][format!("0x{}", "0".repeat(64))],
+            json!(format!("0x{:0>64}", "1"))
+        );
+        assert!(params[0][account].get("state").is_none());
+    }
+
+    #[test]
+    fn block_overrides_need_state_overrides_before_them() {
+        let overrides = CallOverrides {
+            block: BlockOverrides {
+                time: "0x64".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            overrides.params().unwrap(),
+            [json!({}), json!({ "time": "0x64" })]
+        );
+        assert!(CallOverrides::default().params().unwrap().is_empty());
+    }
+
+    #[test]
+    fn log_filter_keeps_topic_positions() {
+        let transfer = format!("0x{}", "dd".repeat(32));
+        let from = format!("0x{}", "0a".repeat(32));
+        let to = format!("0x{}", "0b".repeat(32));
+        let filter = LogFilter {
+            addresses: vec![
+                "0x00000000000000000000000000000000000000aa".to_string(),
+                "0x00000000000000000000000000000000000000bb".to_string(),
+            ],
+            from_block: "0x10".to_string(),
+            topics: vec![
+                vec![transfer.clone()],
+                vec![],
+                vec![from.clone(), to.clone()],
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            filter.param().unwrap(),
+            json!({
+                "fromBlock": "0x10",
+                "address": [
+                    "0x00000000000000000000000000000000000000aa",
+                    "0x00000000000000000000000000000000000000bb"
+                ],
+                "topics": [[transfer], null, [from, to]]
+            })
+        );
+        assert_eq!(LogFilter::default().param().unwrap(), json!({}));
+    }
+
+    #[test]
+    fn log_filter_block_hash_excludes_range() {
+        let filter = LogFilter {
+            block_hash: format!("0x{}", "ab".repeat(32)),
+            to_block: "latest".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(filter.param(), Err(Error::InvalidArgument(_))));
+
+        let filter = LogFilter {
+            block_hash: format!("0x{}", "ab".repeat(32)),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.param().unwrap(),
+            json!({ "blockHash": format!("0x{}", "ab".repeat(32)) })
+        );
+    }
+
+    #[test]
+    fn overrides_need_an_address() {
+        let overrides = CallOverrides {
+            accounts: vec![AccountOverride {
+                code: "0x00".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert!(matches!(overrides.params(), Err(Error::InvalidArgument(_))));
+    }
+}